@@ -0,0 +1,97 @@
+//! times a tick of the 3d simulation with thousands of frogs piling up
+//!
+//! only reports timings, next to those measured when the broadphase was
+//! added, as they vary too much between machines to fail on
+
+use game::sim::{World, Input, Level, Rng, TICK};
+use game::sim::level::FrogSpawn;
+use game::sim::mob::frog;
+use game::sim::rules::Rule;
+use game::sim::terrain::WallSettings;
+
+use macroquad::prelude::*;
+
+use ringbuf::Rb;
+
+use std::time::{Duration, Instant};
+
+/// Frogs, and the milliseconds per tick they took in release when added.
+const BASELINES: [(usize, f32); 3] =
+    [(1_000, 1.2), (5_000, 7.3), (10_000, 14.2)];
+/// Simulated before timing, so the frogs have landed.
+const WARMUP_TICKS: u32 = 60;
+const TICKS: u32 = 120;
+/// Between frogs, which are 1 m wide.
+const SPACING: f32 = 1.2;
+
+fn main() {
+    for (count, baseline) in BASELINES {
+        let mut world = World::new(&level(count), 0);
+        let input = Input { throw: true, ..Input::default() };
+
+        for _ in 0..WARMUP_TICKS { world.step(&input, TICK); }
+
+        let start = Instant::now();
+
+        for _ in 0..TICKS { world.step(&input, TICK); }
+
+        let per_tick = start.elapsed() / TICKS;
+        let share = as_ms(per_tick) / (TICK * 1000.0);
+        let mobs = 1 + world.frogs.len() + world.balls.len();
+
+        println!(
+            "{mobs:>6} mobs: {:>8.3} ms per tick ({:.0}% of a {:.1} ms tick, \
+                {:.0}% of the baseline)",
+            as_ms(per_tick), share * 100.0, TICK * 1000.0,
+            as_ms(per_tick) / baseline * 100.0
+        );
+    }
+}
+
+/// a square of frogs, dropped from slightly different heights so they keep
+/// colliding
+fn level(mob_count: usize) -> Level {
+    let mut rng = Rng::new(0);
+    let side = (mob_count as f32).sqrt().ceil() as usize;
+    let width = side as f32 * SPACING * 2.0;
+    let offset = side as f32 * SPACING / 2.0;
+
+    let frogs = (0..mob_count)
+        .map(|i| {
+            let (x, z) = ((i % side) as f32, (i / side) as f32);
+
+            FrogSpawn {
+                pos: vec3(
+                    x * SPACING - offset,
+                    rng.next_f32() * 3.0,
+                    z * SPACING - offset
+                ),
+                color: GREEN,
+                mass: frog::DEFAULT_MASS,
+                restitution: frog::DEFAULT_RESTITUTION,
+                is_calm: false
+            }
+        })
+        .collect();
+
+    Level {
+        width,
+        heightmap: None,
+        islands: None,
+        wall: WallSettings::for_width(width),
+        spawn: Vec3::ZERO,
+        frogs,
+        flock: None,
+        win: Rule::TimeElapsed(f32::INFINITY),
+        solids: Vec::new(),
+        ammo: None,
+        lives: 1,
+        void_depth: 100.0,
+        hazards: Vec::new(),
+        checkpoints: Vec::new()
+    }
+}
+
+fn as_ms(duration: Duration) -> f32 {
+    duration.as_secs_f32() * 1000.0
+}
@@ -1,7 +1,21 @@
+// Parts are only used by the 3d game.
+#[allow(dead_code)]
+mod bindings;
+
+use bindings::{Binding, Bindings};
 use Direction::*;
 
 use macroquad::prelude::*;
 
+use serde::{Serialize, Deserialize};
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process;
+
+/// Read at startup if it exists.
+const BINDINGS_PATH: &str = "bindings-2d.toml";
+
 const TILE_NUM: (i32, i32) = (11, 7);
 const TILE_SIZE: f32 = (TILE_NUM.0 * 10) as f32;
 
@@ -10,6 +24,7 @@ const DELIM_WIDTH: f32 = TILE_SIZE / 20.0;
 
 #[macroquad::main(conf)]
 async fn main() {
+    let keys = load_bindings();
     let mut grid = Grid::default();
     let mut pawn = Pawn::default();
 
@@ -19,18 +34,17 @@ async fn main() {
         grid.draw();
         pawn.draw();
 
-        if let Some(key) = get_last_key_pressed() {
-            // Pawn movement.
-            match key {
-                KeyCode::W => pawn.step(Up),
-                KeyCode::S => pawn.step(Down),
-                KeyCode::D => pawn.step(Right),
-                KeyCode::A => pawn.step(Left),
-                _ => ()
-            }
+        // Pawn movement.
+        for (action, dir) in [
+            (Action::Up, Up),
+            (Action::Down, Down),
+            (Action::Right, Right),
+            (Action::Left, Left)
+        ] {
+            if keys.is_pressed(action) { pawn.step(dir) }
         }
 
-        if is_key_down(KeyCode::Space) {
+        if keys.is_down(Action::Invert) {
             pawn.invert_colors();
         }
 
@@ -54,6 +68,24 @@ struct Pawn {
     border_color: Color
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    Up,
+    Down,
+    Right,
+    Left,
+    Invert
+}
+
+/// actions left out keep their default bindings
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BindingsFile {
+    keys: BTreeMap<Action, Vec<Binding>>
+}
+
 #[derive(Clone, Copy)]
 enum Direction {
     Up,
@@ -167,6 +199,26 @@ impl Default for Pawn {
     }
 }
 
+/// exits if the file is invalid
+fn load_bindings() -> Bindings<Action> {
+    let keys = Bindings::new([
+        (Action::Up, vec![Binding::Key(KeyCode::W)]),
+        (Action::Down, vec![Binding::Key(KeyCode::S)]),
+        (Action::Right, vec![Binding::Key(KeyCode::D)]),
+        (Action::Left, vec![Binding::Key(KeyCode::A)]),
+        (Action::Invert, vec![Binding::Key(KeyCode::Space)])
+    ]);
+
+    match bindings::read_file::<BindingsFile>(Path::new(BINDINGS_PATH)) {
+        Ok(Some(file)) => keys.with_keys(file.keys),
+        Ok(None) => keys,
+        Err(e) => {
+            eprintln!("{BINDINGS_PATH}: {e}");
+            process::exit(1)
+        }
+    }
+}
+
 fn conf() -> Conf {
     Conf {
         window_title: "future gastrointestinal".to_string(),
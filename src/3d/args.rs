@@ -0,0 +1,78 @@
+use std::env;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+usage: 3d [--level <path>] [--seed <n>] [--bindings <path>]
+          [--record <path> | --replay <path>]
+
+  --level <path>     play a level file instead of the built-in one
+  --seed <n>         start every run from the same random seed
+  --bindings <path>  read and save controls there instead of bindings.toml
+  --record <path>    write the input of each run to a demo file
+  --replay <path>    play a demo file back, and check it ends the same way";
+
+/// command line options
+#[derive(Default)]
+pub struct Args {
+    pub level: Option<PathBuf>,
+    pub seed: Option<u64>,
+    pub bindings: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>
+}
+
+impl Args {
+    pub fn parse() -> Result<Self, String> {
+        let mut args = Self::default();
+        let mut iter = env::args_os().skip(1);
+
+        while let Some(arg) = iter.next() {
+            match arg.to_str() {
+                Some("--level") => {
+                    args.level = Some(value(&mut iter, "--level")?.into());
+                }
+                Some("--seed") => {
+                    let val = value(&mut iter, "--seed")?;
+
+                    args.seed = Some(
+                        val.to_str()
+                            .and_then(|s| s.parse().ok())
+                            .ok_or_else(|| format!(
+                                "--seed must be a whole number, found '{}'",
+                                val.to_string_lossy()
+                            ))?
+                    );
+                }
+                Some("--bindings") => {
+                    args.bindings =
+                        Some(value(&mut iter, "--bindings")?.into());
+                }
+                Some("--record") => {
+                    args.record = Some(value(&mut iter, "--record")?.into());
+                }
+                Some("--replay") => {
+                    args.replay = Some(value(&mut iter, "--replay")?.into());
+                }
+                Some("--help") => return Err(USAGE.to_string()),
+                _ => return Err(format!(
+                    "unexpected argument '{}'\n{USAGE}",
+                    arg.to_string_lossy()
+                ))
+            }
+        }
+
+        if args.record.is_some() && args.replay.is_some() {
+            return Err(format!("cannot both record and replay\n{USAGE}"))
+        }
+
+        Ok(args)
+    }
+}
+
+fn value(
+    iter: &mut impl Iterator<Item = OsString>,
+    name: &str
+) -> Result<OsString, String> {
+    iter.next().ok_or_else(|| format!("{name} requires a value\n{USAGE}"))
+}
@@ -26,7 +26,8 @@ impl Assets {
                 cat: load_texture("assets/cat.png").await?,
                 frog: load_texture("assets/frog.png").await?,
                 // TODO: should probably be a shaded ball
-                ball: Texture2D::from_rgba8(1, 1, &[255, 0, 0, 255])
+                // White, so each kind of ball can tint it.
+                ball: Texture2D::from_rgba8(1, 1, &[255, 255, 255, 255])
             },
             snd: Sounds {
                 woosh: load_sound("assets/woosh.wav").await?,
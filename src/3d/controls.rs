@@ -0,0 +1,278 @@
+use crate::bindings::{self, Binding, Bindings, BindingsError};
+
+use macroquad::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Where the bindings are read from and saved to, unless told otherwise.
+pub const DEFAULT_PATH: &str = "bindings.toml";
+
+/// what the player can do with keys and mouse buttons
+///
+/// looking around always follows the mouse. menus also use fixed keys that
+/// cannot be bound (Enter, C, T and Q), which never apply during play
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    MoveFront,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    Sprint,
+    Jump,
+    Throw,
+    PickUp,
+    Grab,
+    SelectBall,
+    SelectSticky,
+    SelectBouncy,
+    SelectHeavy,
+    SelectGust,
+    ZoomIn,
+    ZoomOut,
+    SuperLeap,
+    Stats,
+    Pause,
+    /// From menus, or once the level is complete.
+    Restart,
+    Quicksave,
+    Quickload,
+    SaveSlot
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SprintMode {
+    /// Sprint while the key is held.
+    #[default]
+    Hold,
+    /// Each press switches sprinting on or off.
+    Toggle
+}
+
+pub struct Controls {
+    pub keys: Bindings<Action>,
+    pub sprint_mode: SprintMode
+}
+
+/// the in-game rebinding screen
+///
+/// one row per action, then one for the sprint mode
+#[derive(Default)]
+pub struct ControlsMenu {
+    selected: usize,
+    /// The next key or button pressed is bound to the selected action.
+    waiting: Option<Rebind>
+}
+
+#[derive(Clone, Copy)]
+enum Rebind {
+    Replace,
+    Add
+}
+
+/// the bindings file, where every field is optional
+#[derive(Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ControlsFile {
+    #[serde(default)]
+    sprint: SprintMode,
+    #[serde(default)]
+    keys: BTreeMap<Action, Vec<Binding>>
+}
+
+impl Action {
+    pub const ALL: [Self; 23] = {
+        use Action::*;
+
+        [
+            MoveFront, MoveBack, MoveLeft, MoveRight,
+            Sprint, Jump, Throw, PickUp, Grab,
+            SelectBall, SelectSticky, SelectBouncy, SelectHeavy, SelectGust,
+            ZoomIn, ZoomOut, SuperLeap,
+            Stats, Pause, Restart, Quicksave, Quickload, SaveSlot
+        ]
+    };
+
+    pub fn label(self) -> &'static str {
+        use Action::*;
+
+        match self {
+            MoveFront => "move forward",
+            MoveBack => "move back",
+            MoveLeft => "move left",
+            MoveRight => "move right",
+            Sprint => "sprint",
+            Jump => "jump",
+            Throw => "throw",
+            PickUp => "pick up",
+            Grab => "grab frog",
+            SelectBall => "select ball",
+            SelectSticky => "select sticky ball",
+            SelectBouncy => "select bouncy ball",
+            SelectHeavy => "select heavy ball",
+            SelectGust => "select gust",
+            ZoomIn => "zoom in",
+            ZoomOut => "zoom out",
+            // It's a secret.
+            SuperLeap => "???",
+            Stats => "stats",
+            Pause => "pause",
+            Restart => "restart level",
+            Quicksave => "quicksave",
+            Quickload => "quickload",
+            SaveSlot => "change save slot"
+        }
+    }
+
+    fn default_bindings(self) -> Vec<Binding> {
+        use Action::*;
+        use Binding::{Key, Mouse};
+
+        vec![match self {
+            MoveFront => Key(KeyCode::W),
+            MoveBack => Key(KeyCode::S),
+            MoveLeft => Key(KeyCode::A),
+            MoveRight => Key(KeyCode::D),
+            Sprint => Key(KeyCode::LeftShift),
+            Jump => Key(KeyCode::Space),
+            Throw => Mouse(MouseButton::Left),
+            PickUp => Key(KeyCode::F),
+            Grab => Mouse(MouseButton::Right),
+            SelectBall => Key(KeyCode::Key1),
+            SelectSticky => Key(KeyCode::Key2),
+            SelectBouncy => Key(KeyCode::Key3),
+            SelectHeavy => Key(KeyCode::Key4),
+            SelectGust => Key(KeyCode::Key5),
+            ZoomIn => Key(KeyCode::E),
+            ZoomOut => Key(KeyCode::Q),
+            SuperLeap => Key(KeyCode::Enter),
+            Stats => Key(KeyCode::Tab),
+            Pause => Key(KeyCode::Escape),
+            Restart => Key(KeyCode::R),
+            Quicksave => Key(KeyCode::F5),
+            Quickload => Key(KeyCode::F9),
+            SaveSlot => Key(KeyCode::F6)
+        }]
+    }
+}
+
+impl Controls {
+    /// the defaults are used if the file does not exist
+    pub fn load(path: &Path) -> Result<Self, BindingsError> {
+        let file: ControlsFile = bindings::read_file(path)?
+            .unwrap_or_default();
+
+        Ok(Self {
+            keys: Self::default().keys.with_keys(file.keys),
+            sprint_mode: file.sprint
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BindingsError> {
+        let file = ControlsFile {
+            sprint: self.sprint_mode,
+            keys: self.keys.keys().clone()
+        };
+
+        bindings::write_file(&file, path)
+    }
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        let defaults = Action::ALL.map(|a| (a, a.default_bindings()));
+
+        Self {
+            keys: Bindings::new(defaults),
+            sprint_mode: SprintMode::default()
+        }
+    }
+}
+
+impl ControlsMenu {
+    const ROWS: usize = Action::ALL.len() + 1;
+
+    /// returns false once the player leaves the menu
+    pub fn update(&mut self, controls: &mut Controls) -> bool {
+        if let Some(rebind) = self.waiting {
+            if is_key_pressed(KeyCode::Escape) {
+                self.waiting = None;
+            } else if let Some(b) = Binding::last_pressed() {
+                let action = Action::ALL[self.selected];
+                let mut keys = match rebind {
+                    Rebind::Replace => Vec::new(),
+                    Rebind::Add => controls.keys.get(action).to_vec()
+                };
+
+                if !keys.contains(&b) { keys.push(b) }
+                controls.keys.set(action, keys);
+                self.waiting = None;
+            }
+
+            return true
+        }
+
+        if is_key_pressed(KeyCode::Escape) { return false }
+
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + Self::ROWS - 1) % Self::ROWS;
+        }
+
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % Self::ROWS;
+        }
+
+        let action = Action::ALL.get(self.selected).copied();
+
+        if is_key_pressed(KeyCode::Enter) {
+            match action {
+                Some(_) => self.waiting = Some(Rebind::Replace),
+                None => controls.sprint_mode = match controls.sprint_mode {
+                    SprintMode::Hold => SprintMode::Toggle,
+                    SprintMode::Toggle => SprintMode::Hold
+                }
+            }
+        }
+
+        if is_key_pressed(KeyCode::Insert) && action.is_some() {
+            self.waiting = Some(Rebind::Add);
+        }
+
+        if is_key_pressed(KeyCode::Backspace) {
+            match action {
+                Some(a) => controls.keys.reset(a),
+                None => controls.sprint_mode = SprintMode::default()
+            }
+        }
+
+        true
+    }
+
+    pub fn selected(&self) -> usize { self.selected }
+
+    /// label and bindings of every row
+    pub fn rows(&self, controls: &Controls) -> Vec<(String, String)> {
+        let sprint_mode = match controls.sprint_mode {
+            SprintMode::Hold => "hold",
+            SprintMode::Toggle => "toggle"
+        };
+
+        Action::ALL.iter()
+            .map(|&a| (a.label().to_string(), controls.keys.describe(a)))
+            .chain([("sprint mode".to_string(), sprint_mode.to_string())])
+            .collect()
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self.waiting {
+            Some(_) => "press a key or mouse button    Esc  cancel",
+            None => "Enter  change    Ins  add    Backspace  default    \
+                     Esc  back"
+        }
+    }
+}
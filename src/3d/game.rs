@@ -0,0 +1,628 @@
+use game::sim::{World, Event, Input, Level, Rng, TICK};
+use game::sim::mob::player;
+use game::sim::save::{Snapshot, SaveError};
+use game::sim::demo::Demo;
+
+use crate::args::Args;
+use crate::assets::{Assets, Textures, Sounds};
+use crate::controls::{self, Action, Controls, ControlsMenu, SprintMode};
+use crate::mouse::Mouse;
+use crate::render::{self, HeightmapMeshes};
+
+use macroquad::prelude::*;
+use macroquad::audio::play_sound_once;
+
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{process, vec};
+
+/// Limits the ticks run in a single frame after a hitch.
+const MAX_FRAME_TIME: f32 = TICK * 10.0;
+
+const SAVE_DIR: &str = "saves";
+const SAVE_SLOTS: u32 = 4;
+/// How long notices stay on screen, in seconds.
+const NOTICE_TIME: f32 = 2.0;
+
+/// drives the simulation from macroquad
+pub struct Game {
+    level: Level,
+    /// Recorded in demos.
+    level_path: Option<PathBuf>,
+    world: World,
+    state: State,
+    /// Used for every run if given, instead of a random one.
+    seed: Option<u64>,
+
+    controls: Controls,
+    /// Where the controls are saved after rebinding.
+    controls_path: PathBuf,
+    controls_menu: ControlsMenu,
+    /// Only used with `SprintMode::Toggle`.
+    is_sprint_toggled: bool,
+    /// Throw was pressed during play, so releasing it throws. Clicks that
+    /// resume play must not throw once let go.
+    is_throw_held: bool,
+
+    mouse: Mouse,
+    input: Input,
+    /// Time not yet simulated.
+    lag: f32,
+    /// Progress from the previous to the current tick, for interpolation.
+    alpha: f32,
+    is_showing_stats: bool,
+    heightmap_meshes: HeightmapMeshes,
+    /// Across restarts.
+    deaths: u32,
+
+    /// From 1 to `SAVE_SLOTS`.
+    save_slot: u32,
+    /// Shown briefly at the top of the screen, with its remaining time.
+    notice: Option<(String, f32)>,
+
+    recording: Option<Recording>,
+    replay: Option<Replay>
+}
+
+/// the current run, written to `path` when it ends
+struct Recording {
+    path: PathBuf,
+    demo: Demo
+}
+
+/// input comes from `demo` instead of the player
+struct Replay {
+    demo: Demo,
+    inputs: vec::IntoIter<Input>
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Title,
+    Playing,
+    /// The game can be resumed, restarted or quit.
+    Paused,
+    /// The level is complete, but the player can keep playing.
+    Victory,
+    /// The player can respawn.
+    Dead,
+    GameOver,
+    /// Reached from the pause menu.
+    Rebinding
+}
+
+impl Game {
+    /// plays back `replay` immediately if given
+    pub fn new(
+        level: Level,
+        controls: Controls,
+        args: Args,
+        replay: Option<Demo>
+    ) -> Self {
+        let world = World::new(&level, 0);
+
+        let mut game = Self {
+            level,
+            level_path: args.level,
+            world,
+            state: State::Title,
+            seed: args.seed,
+
+            controls,
+            controls_path: args.bindings
+                .unwrap_or_else(|| controls::DEFAULT_PATH.into()),
+            controls_menu: ControlsMenu::default(),
+            is_sprint_toggled: false,
+            is_throw_held: false,
+
+            mouse: Mouse::read(),
+            input: Input::default(),
+            lag: 0.0,
+            alpha: 0.0,
+            is_showing_stats: false,
+            heightmap_meshes: HeightmapMeshes::default(),
+            deaths: 0,
+
+            save_slot: 1,
+            notice: None,
+
+            recording: args.record.map(|path| Recording {
+                path,
+                demo: Demo::new(0, None)
+            }),
+            replay: replay.map(|demo| Replay {
+                demo,
+                inputs: Vec::new().into_iter()
+            })
+        };
+
+        if game.replay.is_some() { game.restart() }
+
+        // Closing the window ends the run, which writes the demo first.
+        prevent_quit();
+
+        game
+    }
+
+    /// returns false once the player quits
+    pub fn run_frame(&mut self, assets: &Assets) -> bool {
+        use State::*;
+
+        self.mouse.update();
+        self.update_notice();
+
+        let is_running = match self.state {
+            Title => {
+                if is_key_pressed(KeyCode::Enter)
+                    || is_mouse_button_pressed(MouseButton::Left)
+                {
+                    self.restart();
+                }
+
+                !is_key_pressed(KeyCode::Escape)
+            }
+            Playing | Victory => {
+                if self.controls.keys.is_pressed(Action::Pause) {
+                    self.set_state(Paused);
+                } else {
+                    if self.controls.keys.is_pressed(Action::Stats) {
+                        self.is_showing_stats = !self.is_showing_stats;
+                    }
+
+                    self.handle_save_input();
+                    if self.replay.is_none() { self.handle_input() }
+                    self.update(&assets.snd);
+                }
+
+                true
+            }
+            Paused => {
+                if self.controls.keys.is_pressed(Action::Pause)
+                    || is_key_pressed(KeyCode::Enter)
+                {
+                    self.resume();
+                }
+
+                if is_key_pressed(KeyCode::C) {
+                    self.controls_menu = ControlsMenu::default();
+                    self.set_state(Rebinding);
+                }
+
+                self.handle_save_input();
+                self.handle_menu_input()
+            }
+            Dead => {
+                if is_key_pressed(KeyCode::Enter)
+                    || is_mouse_button_pressed(MouseButton::Left)
+                {
+                    self.resume();
+                    // Respawning happens during the next tick.
+                    self.input.respawn = true;
+                }
+
+                self.handle_menu_input()
+            }
+            GameOver => self.handle_menu_input(),
+            Rebinding => {
+                if !self.controls_menu.update(&mut self.controls) {
+                    self.save_controls();
+                    self.set_state(Paused);
+                }
+
+                true
+            }
+        };
+
+        if !is_running || is_quit_requested() {
+            self.end_run();
+            return false
+        }
+
+        self.draw(&assets.txtr);
+
+        true
+    }
+}
+
+impl Game {
+    fn set_state(&mut self, state: State) {
+        let is_playing = matches!(state, State::Playing | State::Victory);
+
+        set_cursor_grab(is_playing);
+        show_mouse(!is_playing);
+
+        // Forget what happened while the cursor was released.
+        self.mouse = Mouse::read();
+        self.input = Input::default();
+        self.is_throw_held = false;
+        self.state = state;
+    }
+
+    /// rebuilds the world from the level and starts playing
+    fn restart(&mut self) {
+        self.end_run();
+
+        let seed = match &mut self.replay {
+            Some(r) => {
+                r.inputs = r.demo.inputs().collect::<Vec<_>>().into_iter();
+                r.demo.seed()
+            }
+            None => self.seed.unwrap_or_else(random_seed)
+        };
+
+        if let Some(r) = &mut self.recording {
+            r.demo = Demo::new(seed, self.level_path.clone());
+        }
+
+        self.world = World::new(&self.level, seed);
+        self.is_sprint_toggled = false;
+        self.lag = 0.0;
+        self.alpha = 0.0;
+        self.set_state(State::Playing);
+    }
+
+    /// writes the recording of the current run, if any, and starts over
+    fn end_run(&mut self) {
+        self.write_recording();
+
+        if let Some(r) = &mut self.recording {
+            r.demo = Demo::new(self.world.seed(), self.level_path.clone());
+        }
+    }
+
+    /// writes the recording of the current run so far, if any
+    ///
+    /// recording goes on, so the file is overwritten if the run does
+    fn write_recording(&mut self) {
+        let Some(r) = &mut self.recording else { return };
+
+        if r.demo.is_empty() { return }
+
+        r.demo.finish(&self.world);
+
+        let msg = match r.demo.write(&r.path) {
+            Ok(()) => format!("Recorded demo to {}", r.path.display()),
+            Err(e) => format!("{}: {e}", r.path.display())
+        };
+
+        println!("{msg}");
+        self.notify(msg);
+    }
+
+    /// checks the final state against the demo, and returns control to the
+    /// player
+    fn end_replay(&mut self) {
+        let Some(r) = self.replay.take() else { return };
+
+        let msg = if r.demo.matches(&self.world) {
+            format!("Replay of {} ticks matches", r.demo.tick_count())
+        } else {
+            format!("Replay of {} ticks DIVERGED", r.demo.tick_count())
+        };
+
+        println!("{msg}");
+        self.notify(msg);
+        self.set_state(State::Paused);
+    }
+
+    fn resume(&mut self) {
+        if self.world.player.is_victorious {
+            self.set_state(State::Victory);
+        } else {
+            self.set_state(State::Playing);
+        }
+    }
+
+    fn save_controls(&mut self) {
+        let path = &self.controls_path;
+
+        let msg = match self.controls.save(path) {
+            Ok(()) => format!("Saved controls to {}", path.display()),
+            Err(e) => format!("{}: {e}", path.display())
+        };
+
+        self.notify(msg);
+    }
+
+    /// quicksave, quickload and slot selection
+    fn handle_save_input(&mut self) {
+        let keys = &self.controls.keys;
+
+        let (is_changing_slot, is_saving, is_loading) = (
+            keys.is_pressed(Action::SaveSlot),
+            keys.is_pressed(Action::Quicksave),
+            keys.is_pressed(Action::Quickload)
+        );
+
+        if is_changing_slot {
+            self.save_slot = self.save_slot % SAVE_SLOTS + 1;
+            self.notify(format!("Save slot {}", self.save_slot));
+        }
+
+        if is_saving {
+            let msg = match self.world.snapshot().write(&self.save_path()) {
+                Ok(()) => format!("Saved to slot {}", self.save_slot),
+                Err(e) => e.to_string()
+            };
+
+            self.notify(msg);
+        }
+
+        if is_loading {
+            let msg = match self.quickload() {
+                Ok(()) => format!("Loaded slot {}", self.save_slot),
+                Err(e) => e.to_string()
+            };
+
+            self.notify(msg);
+        }
+    }
+
+    /// the world is left untouched on failure
+    fn quickload(&mut self) -> Result<(), SaveError> {
+        if self.recording.is_some() || self.replay.is_some() {
+            return Err(SaveError::Invalid(
+                "cannot load while recording or replaying a demo".into()
+            ))
+        }
+
+        let snap = Snapshot::read(&self.save_path())?;
+        // The seed comes from the save.
+        let mut world = World::new(&self.level, 0);
+
+        world.restore(&snap)?;
+        self.world = world;
+        self.lag = 0.0;
+        self.alpha = 0.0;
+
+        if self.world.is_player_dead() && self.world.lives() == 0 {
+            self.set_state(State::GameOver);
+        } else if self.world.is_player_dead() {
+            self.set_state(State::Dead);
+        } else {
+            self.resume();
+        }
+
+        Ok(())
+    }
+
+    fn save_path(&self) -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(format!("slot{}.toml", self.save_slot))
+    }
+
+    fn notify(&mut self, msg: String) {
+        self.notice = Some((msg, NOTICE_TIME));
+    }
+
+    fn update_notice(&mut self) {
+        if let Some((_, time_left)) = &mut self.notice {
+            *time_left -= get_frame_time();
+
+            if *time_left <= 0.0 { self.notice = None }
+        }
+    }
+
+    /// returns false once the player quits
+    fn handle_menu_input(&mut self) -> bool {
+        if self.controls.keys.is_pressed(Action::Restart) { self.restart() }
+
+        if is_key_pressed(KeyCode::T) {
+            self.end_run();
+            self.set_state(State::Title);
+        }
+
+        !is_key_pressed(KeyCode::Q)
+    }
+
+    /// gathers input for the next tick
+    fn handle_input(&mut self) {
+        use player::{Direction::*, ZoomKind::*, Projectile};
+
+        let (input, mouse) = (&mut self.input, &self.mouse);
+        let (keys, sprint_mode) =
+            (&self.controls.keys, self.controls.sprint_mode);
+
+        if mouse.has_moved() {
+            input.look += mouse.pos_delta();
+        }
+
+        input.zoom = if keys.is_down(Action::ZoomIn) {
+            Some(In)
+        } else if keys.is_down(Action::ZoomOut) {
+            Some(Out)
+        } else {
+            None
+        };
+
+        input.is_sprinting = match sprint_mode {
+            SprintMode::Hold => keys.is_down(Action::Sprint),
+            SprintMode::Toggle => {
+                if keys.is_pressed(Action::Sprint) {
+                    self.is_sprint_toggled = !self.is_sprint_toggled;
+                }
+
+                self.is_sprint_toggled
+            }
+        };
+
+        input.moves.clear();
+
+        for (action, dir) in [
+            (Action::MoveFront, Front),
+            (Action::MoveBack, Back),
+            (Action::MoveRight, Right),
+            (Action::MoveLeft, Left)
+        ] {
+            if keys.is_down(action) { input.moves.push(dir) }
+        }
+
+        for (action, projectile) in [
+            (Action::SelectBall, Projectile::Ball),
+            (Action::SelectSticky, Projectile::Sticky),
+            (Action::SelectBouncy, Projectile::Bouncy),
+            (Action::SelectHeavy, Projectile::Heavy),
+            (Action::SelectGust, Projectile::Gust)
+        ] {
+            if keys.is_pressed(action) { input.select = Some(projectile) }
+        }
+
+        if keys.is_pressed(Action::Throw) { self.is_throw_held = true }
+
+        input.is_charging = self.is_throw_held && keys.is_down(Action::Throw);
+
+        if self.is_throw_held && keys.is_released(Action::Throw) {
+            input.throw = true;
+            self.is_throw_held = false;
+        }
+        if keys.is_pressed(Action::PickUp) { input.pick_up = true }
+
+        input.is_grabbing = keys.is_down(Action::Grab);
+
+        if keys.is_pressed(Action::Grab) { input.grab = true }
+        if keys.is_pressed(Action::Jump) { input.jump = true }
+        // for testing
+        if keys.is_pressed(Action::SuperLeap) { input.super_leap = true }
+
+        if self.state == State::Victory && keys.is_pressed(Action::Restart) {
+            self.restart();
+        }
+    }
+
+    /// runs as many ticks as the elapsed time allows
+    fn update(&mut self, snd: &Sounds) {
+        self.lag += get_frame_time().min(MAX_FRAME_TIME);
+
+        let mut has_died = false;
+
+        while self.lag >= TICK {
+            if let Some(r) = &mut self.replay {
+                let Some(input) = r.inputs.next() else {
+                    self.end_replay();
+                    return
+                };
+
+                self.input = input;
+            }
+
+            if let Some(r) = &mut self.recording {
+                r.demo.record(&self.input);
+            }
+
+            for e in self.world.step(&self.input, TICK) {
+                if e == Event::Death {
+                    self.deaths += 1;
+                    has_died = true;
+                }
+
+                play_event_sound(e, snd);
+            }
+
+            self.input.consume();
+            self.lag -= TICK;
+        }
+
+        self.alpha = self.lag / TICK;
+
+        let world = &self.world;
+
+        // Replays respawn by themselves.
+        if has_died && self.replay.is_none() {
+            if world.lives() == 0 {
+                self.end_run();
+                self.set_state(State::GameOver);
+            } else {
+                self.set_state(State::Dead);
+            }
+        } else if self.state == State::Playing && world.player.is_victorious {
+            // The player may keep playing, or close the game right away.
+            self.write_recording();
+            self.set_state(State::Victory);
+        }
+    }
+
+    /// returns with cam set to default
+    fn draw(&mut self, txtr: &Textures) {
+        use State::*;
+
+        if self.state == Title {
+            clear_background(DARKGRAY);
+            render::draw_menu("future gastrointestinal treedee", &[
+                "Enter / click  play",
+                "Esc  quit"
+            ]);
+
+            return
+        }
+
+        render::draw_world(
+            &self.world, txtr, &mut self.heightmap_meshes,
+            self.alpha, self.is_showing_stats
+        );
+
+        let keys = &self.controls.keys;
+        let restart =
+            format!("{}  restart level", keys.describe(Action::Restart));
+
+        match self.state {
+            Paused => render::draw_menu("PAUSED", &[
+                &format!("{}  resume", keys.describe(Action::Pause)),
+                &format!(
+                    "{} / {}  save / load slot {}",
+                    keys.describe(Action::Quicksave),
+                    keys.describe(Action::Quickload),
+                    self.save_slot
+                ),
+                &format!("{}  change slot", keys.describe(Action::SaveSlot)),
+                "C  controls",
+                &restart,
+                "T  title screen",
+                "Q  quit"
+            ]),
+            Victory => render::draw_hint(&format!(
+                "{}  play again", keys.describe(Action::Restart)
+            )),
+            Dead => render::draw_menu("YOU DIED", &[
+                &format!("lives left: {}", self.world.lives()),
+                &format!("deaths this session: {}", self.deaths),
+                "Enter / click  respawn",
+                &restart,
+                "T  title screen",
+                "Q  quit"
+            ]),
+            GameOver => render::draw_menu("GAME OVER", &[
+                &format!("deaths this session: {}", self.deaths),
+                &restart,
+                "T  title screen",
+                "Q  quit"
+            ]),
+            Rebinding => render::draw_controls(
+                &self.controls_menu.rows(&self.controls),
+                self.controls_menu.selected(),
+                self.controls_menu.hint()
+            ),
+            Title | Playing => ()
+        }
+
+        if let Some((msg, _)) = &self.notice {
+            render::draw_notice(msg);
+        }
+    }
+}
+
+/// differs between runs and processes
+fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos() as u64);
+
+    // Spread similar times over the whole range.
+    Rng::new(nanos ^ (process::id() as u64) << 32).next_u64()
+}
+
+fn play_event_sound(event: Event, snd: &Sounds) {
+    match event {
+        Event::Throw => play_sound_once(snd.woosh),
+        Event::Croak => play_sound_once(snd.croak),
+        Event::PickUp | Event::Checkpoint | Event::Death => ()
+    }
+}
@@ -0,0 +1,635 @@
+use crate::assets::Textures;
+
+use game::sim::World;
+use game::sim::rules::Status;
+use game::sim::zone::Zone;
+use game::sim::solid::Solid;
+use game::sim::trajectory::{Trajectory, Impact};
+use game::sim::heightmap::Heightmap;
+use game::sim::island::Island;
+use game::sim::terrain::Terrain;
+use game::sim::mob::{Mob, frog::Frog, ball::{self, Ball}, gust::Gust};
+use game::sim::mob::player::{Player, Projectile};
+
+use macroquad::prelude::*;
+use macroquad::models;
+
+use ringbuf::Rb;
+
+/// `alpha` is the progress from the previous to the current tick
+///
+/// returns with cam set to default
+pub fn draw_world(
+    world: &World,
+    txtr: &Textures,
+    meshes: &mut HeightmapMeshes,
+    alpha: f32,
+    is_showing_stats: bool
+) {
+    set_camera(&world.player.cam(alpha));
+    draw_terrain(&world.terrain, txtr, meshes, world.seed());
+    world.terrain.solids().iter().for_each(draw_solid);
+    world.frogs.iter().for_each(|f| draw_frog(f, txtr, alpha));
+    world.balls.iter().for_each(|b| draw_ball(b, txtr, alpha));
+    world.gusts.iter().for_each(|g| draw_gust(g, alpha));
+
+    if let Some(i) = world.carried() {
+        let frog = &world.frogs[i];
+        let centre = frog.lerp_pos(alpha) + frog.dim() / 2.0;
+
+        draw_cube_wires(centre, frog.dim() * 1.1, SKYBLUE);
+    } else if world.player.charge() > 0.0 && world.ammo() != Some(0) {
+        if let Some(t) = world.predict_throw() { draw_trajectory(&t, world) }
+    }
+
+    draw_zones(world);
+
+    set_default_camera();
+    draw_crosshair(world);
+    draw_view(&world.player, &world.terrain, is_showing_stats);
+    if is_showing_stats { draw_world_stats(world) }
+    draw_status(world.goals());
+    draw_lives(world);
+    draw_ammo(world);
+}
+
+/// translucent, so drawn last
+fn draw_zones(world: &World) {
+    const HAZARD: Color = Color::new(1.0, 0.0, 0.0, 0.3);
+    const CHECKPOINT: Color = Color::new(0.0, 1.0, 0.0, 0.2);
+    const CHECKPOINT_ACTIVE: Color = Color::new(1.0, 1.0, 0.0, 0.3);
+
+    for h in world.hazards() {
+        draw_zone(h, HAZARD);
+    }
+
+    for c in world.checkpoints() {
+        let color = if c.floor() == world.checkpoint() {
+            CHECKPOINT_ACTIVE
+        } else {
+            CHECKPOINT
+        };
+
+        draw_zone(c, color);
+    }
+}
+
+/// highlighted when on a frog the player can grab
+///
+/// requires default cam to be set: `set_default_camera()`
+fn draw_crosshair(world: &World) {
+    const SIZE: f32 = 8.0;
+
+    let (x, y) = (screen_width() / 2.0, screen_height() / 2.0);
+    let is_on_frog =
+        world.carried().is_none() && world.grabbable_frog().is_some();
+    let color = if is_on_frog { YELLOW } else { WHITE };
+
+    draw_line(x - SIZE, y, x + SIZE, y, 2.0, color);
+    draw_line(x, y - SIZE, x, y + SIZE, 2.0, color);
+}
+
+/// below the player stats
+///
+/// requires default cam to be set: `set_default_camera()`
+fn draw_world_stats(world: &World) {
+    let seed = format!("Seed: {}", world.seed());
+    let resting = world.balls.iter().filter(|b| b.is_resting()).count();
+    let balls = format!("Balls: {} ({resting} resting)", world.balls.len());
+
+    draw_text(&seed, 10.0, 95.0, 30.0, WHITE);
+    draw_text(&balls, 10.0, 130.0, 30.0, WHITE);
+}
+
+/// requires default cam to be set: `set_default_camera()`
+fn draw_lives(world: &World) {
+    let text = format!("Lives: {}", world.lives());
+
+    draw_text(&text, 10.0, screen_height() - 20.0, 30.0, WHITE);
+}
+
+/// above the lives, unless unlimited
+///
+/// requires default cam to be set: `set_default_camera()`
+fn draw_ammo(world: &World) {
+    let Some(ammo) = world.ammo() else { return };
+    let text = format!("Balls: {ammo}");
+
+    draw_text(&text, 10.0, screen_height() - 55.0, 30.0, WHITE);
+}
+
+fn draw_zone(zone: &Zone, color: Color) {
+    let dim = zone.dim();
+
+    draw_cube(zone.min + dim / 2.0, dim, None, color);
+    draw_cube_wires(zone.min + dim / 2.0, dim, color);
+}
+
+/// shaded darker towards the bottom, with dark edges
+fn draw_solid(solid: &Solid) {
+    const TOP: Color = LIGHTGRAY;
+    const BOTTOM: Color = GRAY;
+    const EDGES: Color = DARKGRAY;
+
+    let (min, max) = (solid.min, solid.max);
+    let footprint = [
+        vec3(min.x, 0.0, min.z),
+        vec3(max.x, 0.0, min.z),
+        vec3(max.x, 0.0, max.z),
+        vec3(min.x, 0.0, max.z)
+    ];
+    let bottom = footprint.map(|c| vec3(c.x, min.y, c.z));
+    let top = footprint.map(|c| vec3(c.x, solid.top_over(c, c), c.z));
+
+    let vertices = bottom.iter()
+        .map(|&p| (p, BOTTOM))
+        .chain(top.iter().map(|&p| (p, TOP)))
+        .map(|(position, color)| models::Vertex {
+            position,
+            uv: Vec2::ZERO,
+            color
+        })
+        .collect();
+
+    // Two triangles per face: the bottom and top, then the sides.
+    let mut indices = vec![0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7];
+
+    for i in 0..4 {
+        let j = (i + 1) % 4;
+
+        indices.extend([i, j, j + 4, i, j + 4, i + 4]);
+    }
+
+    draw_mesh(&Mesh { vertices, indices, texture: None });
+
+    for i in 0..4 {
+        let j = (i + 1) % 4;
+
+        draw_line_3d(bottom[i], bottom[j], EDGES);
+        draw_line_3d(top[i], top[j], EDGES);
+        draw_line_3d(bottom[i], top[i], EDGES);
+    }
+}
+
+/// a dotted arc, with the landing point or the frog hit marked
+fn draw_trajectory(trajectory: &Trajectory, world: &World) {
+    const ARC: Color = Color::new(1.0, 1.0, 1.0, 0.6);
+    const MARK: Color = YELLOW;
+    // Ticks per dash.
+    const DASH: usize = 2;
+
+    for (i, pair) in trajectory.points.windows(2).enumerate() {
+        if (i / DASH).is_multiple_of(2) {
+            draw_line_3d(pair[0], pair[1], ARC);
+        }
+    }
+
+    match trajectory.end {
+        Some(Impact::Ground(pos)) => {
+            draw_cube_wires(pos, vec3(0.6, 0.02, 0.6), MARK);
+        }
+        Some(Impact::Frog(i)) => {
+            let frog = &world.frogs[i];
+
+            draw_cube_wires(frog.centre(), frog.dim() * 1.1, MARK);
+        }
+        None => ()
+    }
+}
+
+/// lists every goal and whether it is met, in the top right
+///
+/// requires default cam to be set: `set_default_camera()`
+fn draw_status(status: &Status) {
+    const FONT_SIZE: f32 = 24.0;
+    const WIDTH: f32 = 320.0;
+
+    let mut y = 25.0;
+
+    draw_status_line(status, screen_width() - WIDTH, &mut y, FONT_SIZE);
+}
+
+fn draw_status_line(status: &Status, x: f32, y: &mut f32, font_size: f32) {
+    let (mark, color) = if status.is_met {
+        ("[x]", GREEN)
+    } else {
+        ("[ ]", WHITE)
+    };
+
+    let text = if status.children.is_empty() {
+        format!("{mark} {}", status.label)
+    } else {
+        format!("{mark} {} ({:.0}%)", status.label, status.progress * 100.0)
+    };
+
+    draw_text(&text, x, *y, font_size, color);
+    *y += font_size;
+
+    for c in &status.children {
+        draw_status_line(c, x + font_size, y, font_size);
+    }
+}
+
+/// `seed` is that of the world, for `meshes` to tell worlds apart
+fn draw_terrain(
+    terrain: &Terrain,
+    txtr: &Textures,
+    meshes: &mut HeightmapMeshes,
+    seed: u64
+) {
+    let grid_colors = terrain.grid_colors();
+
+    clear_background(DARKGRAY);
+
+    match (terrain.heightmap(), terrain.islands()) {
+        (Some(_), _) => meshes.draw(terrain, seed),
+        (None, Some(islands)) => {
+            islands.iter().for_each(|i| draw_island(i, grid_colors));
+        }
+        (None, None) => draw_grid(
+            // Spacing is 1, so coordinates match grid position.
+            terrain.width() as u32, 1.0,
+            grid_colors.0, grid_colors.1
+        )
+    }
+
+    draw_cube(
+        terrain.wall_pos(), terrain.wall_dim(),
+        txtr.cat, terrain.wall_color()
+    );
+    //draw_plane()
+}
+
+/// heightmap meshes, which are slow to build, kept from frame to frame
+#[derive(Default)]
+pub struct HeightmapMeshes {
+    /// Seed of the world they were built for, which lays out the islands
+    /// cutting into the heightmap.
+    seed: Option<u64>,
+    meshes: Vec<Mesh>,
+    /// Lighting of every vertex, by mesh.
+    shades: Vec<Vec<f32>>,
+    /// Vertices are lit shades of it.
+    color: Color
+}
+
+impl HeightmapMeshes {
+    /// in the second of the grid colors, tinted with the first, and lit from
+    /// above
+    ///
+    /// builds the meshes again only for another world, and recolors them
+    /// only when the grid colors change
+    fn draw(&mut self, terrain: &Terrain, seed: u64) {
+        const TINT: f32 = 0.2;
+
+        let Some(map) = terrain.heightmap() else { return };
+
+        if self.seed != Some(seed) {
+            self.build(map, |x, z| terrain.is_land(x, z));
+            self.seed = Some(seed);
+        }
+
+        let (accent, base) = terrain.grid_colors();
+        let mix = |b: f32, a: f32| b + (a - b) * TINT;
+        let color = Color::new(
+            mix(base.r, accent.r),
+            mix(base.g, accent.g),
+            mix(base.b, accent.b),
+            1.0
+        );
+
+        if color != self.color { self.recolor(color) }
+
+        self.meshes.iter().for_each(draw_mesh);
+    }
+
+    /// cells whose centre is not on land are left out
+    fn build<F>(&mut self, map: &Heightmap, is_land: F)
+        where F: Fn(f32, f32) -> bool
+    {
+        // Cells along each side of a chunk, which fits in a single draw call.
+        const CHUNK: usize = 28;
+
+        let light = vec3(0.3, 1.0, 0.5).normalize();
+        let (cols, rows) = (map.cols(), map.rows());
+
+        self.meshes.clear();
+        self.shades.clear();
+
+        for row_start in (0..rows - 1).step_by(CHUNK) {
+            for col_start in (0..cols - 1).step_by(CHUNK) {
+                let (row_end, col_end) = (
+                    (row_start + CHUNK).min(rows - 1),
+                    (col_start + CHUNK).min(cols - 1)
+                );
+                let chunk_cols = col_end - col_start + 1;
+
+                let (vertices, shades) = (row_start..=row_end)
+                    .flat_map(|r| (col_start..=col_end).map(move |c| (c, r)))
+                    .map(|(c, r)| {
+                        let position = map.sample(c, r);
+                        let normal = map.normal_at(position.x, position.z);
+                        let vertex = models::Vertex {
+                            position,
+                            uv: Vec2::ZERO,
+                            color: WHITE
+                        };
+
+                        (vertex, 0.6 + 0.4 * normal.dot(light).max(0.0))
+                    })
+                    .unzip();
+
+                // Two triangles per cell.
+                let mut indices = Vec::new();
+
+                for r in 0..row_end - row_start {
+                    for c in 0..col_end - col_start {
+                        let centre = (
+                            map.sample(col_start + c, row_start + r)
+                            + map.sample(col_start + c + 1, row_start + r + 1)
+                        ) / 2.0;
+
+                        if !is_land(centre.x, centre.z) { continue }
+
+                        let i = (r * chunk_cols + c) as u16;
+                        let below = i + chunk_cols as u16;
+
+                        indices.extend([
+                            i, i + 1, below + 1,
+                            i, below + 1, below
+                        ]);
+                    }
+                }
+
+                self.meshes.push(Mesh { vertices, indices, texture: None });
+                self.shades.push(shades);
+            }
+        }
+
+        // Not colored yet.
+        self.color = Color::default();
+    }
+
+    fn recolor(&mut self, color: Color) {
+        for (mesh, shades) in self.meshes.iter_mut().zip(&self.shades) {
+            for (vertex, shade) in mesh.vertices.iter_mut().zip(shades) {
+                vertex.color = Color::new(
+                    color.r * shade, color.g * shade, color.b * shade, 1.0
+                );
+            }
+        }
+
+        self.color = color;
+    }
+}
+
+/// flat, in the second of the grid `colors` and outlined with the first
+fn draw_island(island: &Island, colors: (Color, Color)) {
+    let (outline, fill) = colors;
+    let corners: Vec<_> = island.outline().iter()
+        .map(|c| vec3(c.x, 0.0, c.y))
+        .collect();
+    let centre = vec3(island.centre().x, 0.0, island.centre().y);
+
+    let vertices = [centre].iter()
+        .chain(&corners)
+        .map(|&position| models::Vertex {
+            position,
+            uv: Vec2::ZERO,
+            color: fill
+        })
+        .collect();
+
+    // A fan of triangles around the centre.
+    let count = corners.len() as u16;
+    let indices = (1..=count)
+        .flat_map(|i| [0, i, i % count + 1])
+        .collect();
+
+    draw_mesh(&Mesh { vertices, indices, texture: None });
+
+    for (i, &c) in corners.iter().enumerate() {
+        draw_line_3d(c, corners[(i + 1) % corners.len()], outline);
+    }
+}
+
+fn draw_frog(frog: &Frog, txtr: &Textures, alpha: f32) {
+    draw_mob(frog, txtr.frog, frog.color(), alpha);
+}
+
+fn draw_ball(ball: &Ball, txtr: &Textures, alpha: f32) {
+    draw_mob(ball, txtr.ball, ball_color(ball.kind()), alpha);
+}
+
+fn ball_color(kind: ball::Kind) -> Color {
+    match kind {
+        ball::Kind::Regular => RED,
+        ball::Kind::Sticky => LIME,
+        ball::Kind::Bouncy => MAGENTA,
+        ball::Kind::Heavy => DARKGRAY
+    }
+}
+
+/// a faint ring around the front of the cone, between the previous and
+/// current tick
+fn draw_gust(gust: &Gust, alpha: f32) {
+    const COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.5);
+    const SEGMENTS: usize = 16;
+
+    let centre = gust.lerp_pos(alpha) + gust.dim() / 2.0;
+    let axis = gust.vel().normalize();
+    let radius = gust.radius_at((centre - gust.origin()).length());
+    let side = axis.any_orthonormal_vector() * radius;
+    let up = axis.cross(side);
+
+    let point = |i: usize| {
+        let angle = i as f32 / SEGMENTS as f32 * std::f32::consts::TAU;
+
+        centre + side * angle.cos() + up * angle.sin()
+    };
+
+    for i in 0..SEGMENTS {
+        draw_line_3d(point(i), point(i + 1), COLOR);
+    }
+}
+
+/// draws additional elements of player vision (stats, shroud...)
+///
+/// requires default cam to be set: `set_default_camera()`, as do the
+/// functions it calls
+fn draw_view(player: &Player, terrain: &Terrain, is_showing_stats: bool) {
+    draw_void_shroud(player, terrain.void_depth());
+
+    if is_showing_stats {
+        draw_player_stats(player);
+    }
+
+    draw_projectile(player);
+
+    if player.charge() > 0.0 {
+        draw_charge_meter(player);
+    }
+
+    if player.is_victorious {
+        draw_victory_text(player, terrain);
+    }
+}
+
+/// above the ammo and lives
+fn draw_projectile(player: &Player) {
+    let text = format!("Throwing: {}", projectile_label(player.projectile()));
+
+    draw_text(&text, 10.0, screen_height() - 90.0, 30.0, WHITE);
+}
+
+/// fills up towards the right, under the centre of the screen
+fn draw_charge_meter(player: &Player) {
+    const WIDTH: f32 = 200.0;
+    const HEIGHT: f32 = 12.0;
+
+    let x = (screen_width() - WIDTH) / 2.0;
+    let y = screen_height() / 2.0 + 40.0;
+    let color = Color::new(1.0, 1.0 - player.charge(), 0.0, 1.0);
+
+    draw_rectangle(x, y, WIDTH * player.charge(), HEIGHT, color);
+    draw_rectangle_lines(x, y, WIDTH, HEIGHT, 2.0, WHITE);
+}
+
+/// draws progressively more intense darkness in void, from halfway down to
+/// where the player dies
+fn draw_void_shroud(player: &Player, void_depth: f32) {
+    let depth_ratio = -player.pos().y / void_depth * 2.0 - 1.0;
+
+    if depth_ratio > 0.0 {
+        draw_rectangle(
+            0.0, 0.0, screen_width(), screen_height(),
+            Color::new(0.0, 0.0, 0.0, depth_ratio)
+        );
+    }
+}
+
+fn draw_player_stats(player: &Player) {
+    let [x, y, z] = player.pos().to_array();
+    let pos_text = format!("Position: {x:.2} / {y:.2} / {z:.2}");
+
+    // Ignore vertical velocity.
+    let vel = player.vel();
+    let vel_h = vec3(vel.x, 0.0, vel.z);
+    let vel_text = format!("Velocity: {:.2} m/s", vel_h.length());
+
+    draw_text(&pos_text, 10.0, 25.0, 30.0, WHITE);
+    draw_text(&vel_text, 10.0, 60.0, 30.0, WHITE);
+}
+
+fn draw_victory_text(player: &Player, terrain: &Terrain) {
+    // From the bottom of the screen.
+    let y = screen_height() - 50.0;
+
+    let color = if player.is_in_void(terrain) {
+        RED
+    } else {
+        YELLOW
+    };
+
+    draw_text("VICTORY", 60.0, y, 175.0, color);
+}
+
+fn projectile_label(projectile: Projectile) -> &'static str {
+    match projectile {
+        Projectile::Ball => "ball",
+        Projectile::Sticky => "sticky ball",
+        Projectile::Bouncy => "bouncy ball",
+        Projectile::Heavy => "heavy ball",
+        Projectile::Gust => "gust"
+    }
+}
+
+/// draws a textured cube between the previous and current tick
+fn draw_mob<M: Mob>(mob: &M, txtr: Texture2D, color: Color, alpha: f32) {
+    let dim = mob.dim();
+
+    draw_cube(mob.lerp_pos(alpha) + dim / 2.0, dim, txtr, color);
+}
+
+/// darkens the screen and draws centred text
+///
+/// requires default cam to be set: `set_default_camera()`
+pub fn draw_menu(title: &str, lines: &[&str]) {
+    const TITLE_SIZE: f32 = 80.0;
+    const LINE_SIZE: f32 = 32.0;
+
+    draw_rectangle(
+        0.0, 0.0, screen_width(), screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6)
+    );
+
+    let mut y = screen_height() / 3.0;
+
+    draw_centred_text(title, y, TITLE_SIZE, YELLOW);
+    y += TITLE_SIZE;
+
+    for l in lines {
+        draw_centred_text(l, y, LINE_SIZE, WHITE);
+        y += LINE_SIZE * 1.5;
+    }
+}
+
+/// draws the rebinding screen, with the `selected` row highlighted
+///
+/// requires default cam to be set: `set_default_camera()`
+pub fn draw_controls(rows: &[(String, String)], selected: usize, hint: &str) {
+    const TITLE_SIZE: f32 = 60.0;
+    const ROW_SIZE: f32 = 24.0;
+
+    draw_rectangle(
+        0.0, 0.0, screen_width(), screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.8)
+    );
+
+    let mut y = 70.0;
+
+    draw_centred_text("CONTROLS", y, TITLE_SIZE, YELLOW);
+    y += TITLE_SIZE;
+
+    // Labels on the left of the centre, bindings on the right.
+    let binding_x = screen_width() / 2.0;
+    let label_x = binding_x - 250.0;
+
+    for (i, (label, binding)) in rows.iter().enumerate() {
+        let color = if i == selected { YELLOW } else { WHITE };
+
+        draw_text(label, label_x, y, ROW_SIZE, color);
+        draw_text(binding, binding_x, y, ROW_SIZE, color);
+        y += ROW_SIZE * 1.2;
+    }
+
+    draw_hint(hint);
+}
+
+/// draws a line of text at the bottom right of the screen
+///
+/// requires default cam to be set: `set_default_camera()`
+pub fn draw_hint(text: &str) {
+    const FONT_SIZE: f32 = 32.0;
+
+    let width = measure_text(text, None, FONT_SIZE as u16, 1.0).width;
+
+    draw_text(
+        text,
+        screen_width() - width - 20.0, screen_height() - 20.0,
+        FONT_SIZE, WHITE
+    );
+}
+
+/// draws a line of text at the top of the screen
+///
+/// requires default cam to be set: `set_default_camera()`
+pub fn draw_notice(text: &str) {
+    draw_centred_text(text, 40.0, 32.0, YELLOW);
+}
+
+fn draw_centred_text(text: &str, y: f32, font_size: f32, color: Color) {
+    let width = measure_text(text, None, font_size as u16, 1.0).width;
+
+    draw_text(text, (screen_width() - width) / 2.0, y, font_size, color);
+}
@@ -0,0 +1,99 @@
+use macroquad::prelude::*;
+
+/// Side of the cubic cells, a bit larger than most mobs.
+const CELL_SIZE: f32 = 2.0;
+/// Boxes covering more cells are paired with every other box instead.
+const MAX_CELLS: i64 = 64;
+
+/// a uniform grid over boxes, rebuilt every tick, that finds which of them
+/// may overlap
+///
+/// boxes are identified by indices chosen by the caller. results only
+/// depend on the boxes inserted, never on their insertion order
+#[derive(Default)]
+pub struct Grid {
+    /// Every cell covered by each box, sorted.
+    entries: Vec<([i32; 3], u32)>,
+    /// Boxes covering too many cells to insert.
+    large: Vec<u32>,
+    ids: Vec<u32>,
+    pairs: Vec<(u32, u32)>
+}
+
+impl Grid {
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.large.clear();
+        self.ids.clear();
+        self.pairs.clear();
+    }
+
+    /// for a box between the corners `min` and `max`
+    pub fn insert(&mut self, id: u32, min: Vec3, max: Vec3) {
+        let (min, max) = (cell_of(min), cell_of(max));
+        let cell_count = (0..3)
+            .map(|i| (max[i] - min[i]) as i64 + 1)
+            .product::<i64>();
+
+        self.ids.push(id);
+
+        if cell_count > MAX_CELLS {
+            self.large.push(id);
+            return
+        }
+
+        for x in min[0]..=max[0] {
+            for y in min[1]..=max[1] {
+                for z in min[2]..=max[2] {
+                    self.entries.push(([x, y, z], id));
+                }
+            }
+        }
+    }
+
+    /// every pair of boxes sharing a cell, lowest id first, each listed once
+    /// and in increasing order
+    pub fn pairs(&mut self) -> &[(u32, u32)] {
+        self.entries.sort_unstable();
+
+        for cell in self.entries.chunk_by(|a, b| a.0 == b.0) {
+            for (i, &(_, a)) in cell.iter().enumerate() {
+                for &(_, b) in &cell[i + 1..] {
+                    self.pairs.push((a.min(b), a.max(b)));
+                }
+            }
+        }
+
+        for &a in &self.large {
+            for &b in &self.ids {
+                if a != b { self.pairs.push((a.min(b), a.max(b))) }
+            }
+        }
+
+        self.pairs.sort_unstable();
+        self.pairs.dedup();
+
+        &self.pairs
+    }
+}
+
+/// mutable references to two different items of `items`
+///
+/// panics if `i == j`
+pub fn pair_mut<T>(items: &mut [T], i: usize, j: usize) -> (&mut T, &mut T) {
+    assert_ne!(i, j, "a pair needs two different items");
+
+    if i < j {
+        let (head, tail) = items.split_at_mut(j);
+
+        (&mut head[i], &mut tail[0])
+    } else {
+        let (head, tail) = items.split_at_mut(i);
+
+        (&mut tail[0], &mut head[j])
+    }
+}
+
+fn cell_of(pos: Vec3) -> [i32; 3] {
+    (pos / CELL_SIZE).floor().as_ivec3().to_array()
+}
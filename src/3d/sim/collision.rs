@@ -0,0 +1,244 @@
+use super::mob::Mob;
+use super::terrain::Terrain;
+
+use macroquad::prelude::*;
+
+/// how two boxes overlap
+#[derive(Clone, Copy, Debug)]
+pub struct Contact {
+    /// Axis of least overlap, pointing from the first box to the second.
+    pub normal: Vec3,
+    /// Overlap along `normal`.
+    pub depth: f32
+}
+
+/// for boxes given by their minimum corner and dimensions
+///
+/// boxes that merely touch do not overlap
+pub fn box_contact(min_a: Vec3, dim_a: Vec3, min_b: Vec3, dim_b: Vec3)
+    -> Option<Contact>
+{
+    box_contact_along(min_a, dim_a, min_b, dim_b, [true; 3])
+}
+
+/// distance from `origin` along the unit vector `dir` to where the ray
+/// enters the box, and the normal of the face it enters through
+///
+/// the box is given by its minimum corner and dimensions. rays starting
+/// inside it hit it right away, facing back along the ray
+pub fn ray_box(origin: Vec3, dir: Vec3, min: Vec3, dim: Vec3)
+    -> Option<(f32, Vec3)>
+{
+    let (enter, exit, normal) = ray_span(origin, dir, min, min + dim)?;
+
+    (enter <= exit).then_some((enter, normal))
+}
+
+/// distances from `origin` along the unit vector `dir` at which the ray
+/// enters and exits the box between the corners `min` and `max`, and the
+/// normal of the face it enters through, like [`ray_box`]
+///
+/// the ray misses the box if it exits before entering
+pub fn ray_span(origin: Vec3, dir: Vec3, min: Vec3, max: Vec3)
+    -> Option<(f32, f32, Vec3)>
+{
+    let (mut enter, mut exit) = (0.0, f32::INFINITY);
+    let mut normal = -dir;
+
+    for i in 0..3 {
+        if dir[i] == 0.0 {
+            // Parallel to the slab of this axis, so never enters it.
+            if origin[i] < min[i] || origin[i] > max[i] { return None }
+
+            continue
+        }
+
+        let (near, far) = if dir[i] > 0.0 {
+            (min[i], max[i])
+        } else {
+            (max[i], min[i])
+        };
+        let (t_near, t_far) = (
+            (near - origin[i]) / dir[i],
+            (far - origin[i]) / dir[i]
+        );
+
+        if t_near > enter {
+            enter = t_near;
+            normal = Vec3::ZERO;
+            normal[i] = -dir[i].signum();
+        }
+
+        exit = exit.min(t_far);
+    }
+
+    Some((enter, exit, normal))
+}
+
+/// pushes overlapping mobs apart and exchanges their momentum along the
+/// contact normal, conserving it
+///
+/// lighter mobs are pushed further. mobs on the ground are never pushed into
+/// it, as if they were infinitely heavy
+pub fn collide<A, B>(a: &mut A, b: &mut B, terrain: &Terrain)
+    -> Option<Contact>
+    where A: Mob, B: Mob
+{
+    let (is_a_grounded, is_b_grounded) =
+        (a.is_on_ground(terrain), b.is_on_ground(terrain));
+
+    // Mobs standing side by side can only push each other sideways.
+    let axes = [true, !(is_a_grounded && is_b_grounded), true];
+    let contact = box_contact_along(
+        a.pos(), a.dim(), b.pos(), b.dim(), axes
+    )?;
+
+    resolve(a, b, contact, (is_a_grounded, is_b_grounded));
+
+    Some(contact)
+}
+
+/// like [`collide`], but also catches `a` passing through `b` since the
+/// previous tick, for fast mobs like balls
+pub fn collide_swept<A, B>(a: &mut A, b: &mut B, terrain: &Terrain)
+    -> Option<Contact>
+    where A: Mob, B: Mob
+{
+    if let Some(contact) = collide(a, b, terrain) { return Some(contact) }
+
+    let motion_a = a.pos() - a.prev_pos();
+    let motion_b = b.pos() - b.prev_pos();
+    let (time, normal) = sweep_boxes(
+        a.prev_pos(), a.dim(), motion_a - motion_b,
+        b.prev_pos(), b.dim()
+    )?;
+
+    // Move a back to where it hit b, relative to where b is now.
+    let hit_offset = (a.prev_pos() + motion_a * time)
+        - (b.prev_pos() + motion_b * time);
+    let contact = Contact { normal, depth: 0.0 };
+    let grounded = (a.is_on_ground(terrain), b.is_on_ground(terrain));
+
+    a.set_pos(b.pos() + hit_offset);
+    resolve(a, b, contact, grounded);
+
+    Some(contact)
+}
+
+/// earliest time, from 0 to 1, at which box a moving by `motion` hits the
+/// still box b, and the normal of the face it hits
+///
+/// boxes are given by their minimum corner and dimensions. boxes that
+/// already overlap at the start are not considered hit
+pub fn sweep_boxes(
+    min_a: Vec3,
+    dim_a: Vec3,
+    motion: Vec3,
+    min_b: Vec3,
+    dim_b: Vec3
+) -> Option<(f32, Vec3)> {
+    // Sweep the corner of a through b grown by the size of a.
+    let (slab_min, slab_max) = (min_b - dim_a, min_b + dim_b);
+    let (mut enter, mut exit) = (f32::NEG_INFINITY, f32::INFINITY);
+    let mut axis = 0;
+
+    for i in 0..3 {
+        if motion[i] == 0.0 {
+            // Never enters the slab of this axis.
+            if min_a[i] <= slab_min[i] || min_a[i] >= slab_max[i] {
+                return None
+            }
+
+            continue
+        }
+
+        let (mut t_min, mut t_max) = (
+            (slab_min[i] - min_a[i]) / motion[i],
+            (slab_max[i] - min_a[i]) / motion[i]
+        );
+
+        if t_min > t_max { (t_min, t_max) = (t_max, t_min) }
+
+        if t_min > enter {
+            enter = t_min;
+            axis = i;
+        }
+
+        exit = exit.min(t_max);
+    }
+
+    if enter >= exit || !(0.0..=1.0).contains(&enter) { return None }
+
+    let mut normal = Vec3::ZERO;
+
+    normal[axis] = motion[axis].signum();
+
+    Some((enter, normal))
+}
+
+/// pushes the mobs apart by the depth of `contact`, then exchanges momentum
+/// along its normal
+fn resolve<A, B>(
+    a: &mut A,
+    b: &mut B,
+    contact: Contact,
+    grounded: (bool, bool)
+) where A: Mob, B: Mob {
+    let normal = contact.normal;
+    let (is_a_grounded, is_b_grounded) = grounded;
+
+    // Inverse masses, so that the ground can be infinitely heavy.
+    let inv_a = if normal.y > 0.0 && is_a_grounded {
+        0.0
+    } else {
+        1.0 / a.mass()
+    };
+    let inv_b = if normal.y < 0.0 && is_b_grounded {
+        0.0
+    } else {
+        1.0 / b.mass()
+    };
+    let inv_sum = inv_a + inv_b;
+
+    let push = normal * contact.depth / inv_sum;
+
+    a.set_pos(a.pos() - push * inv_a);
+    b.set_pos(b.pos() + push * inv_b);
+
+    let closing_speed = (a.vel() - b.vel()).dot(normal);
+
+    // Only bounce if the mobs are moving towards each other.
+    if closing_speed > 0.0 {
+        let restitution = (a.restitution() + b.restitution()) / 2.0;
+        let impulse = normal * closing_speed * (1.0 + restitution) / inv_sum;
+
+        a.set_vel(a.vel() - impulse * inv_a);
+        b.set_vel(b.vel() + impulse * inv_b);
+    }
+}
+
+/// only considers the `axes` that are true
+fn box_contact_along(
+    min_a: Vec3,
+    dim_a: Vec3,
+    min_b: Vec3,
+    dim_b: Vec3,
+    axes: [bool; 3]
+) -> Option<Contact> {
+    let (max_a, max_b) = (min_a + dim_a, min_b + dim_b);
+    let overlap = max_a.min(max_b) - min_a.max(min_b);
+
+    if overlap.min_element() <= 0.0 { return None }
+
+    let axis = (0..3)
+        .filter(|&i| axes[i])
+        .min_by(|&i, &j| overlap[i].total_cmp(&overlap[j]))?;
+
+    // Push along the axis from the centre of a towards the centre of b.
+    let centre_delta = (min_b + dim_b / 2.0) - (min_a + dim_a / 2.0);
+    let mut normal = Vec3::ZERO;
+
+    normal[axis] = if centre_delta[axis] < 0.0 { -1.0 } else { 1.0 };
+
+    Some(Contact { normal, depth: overlap[axis] })
+}
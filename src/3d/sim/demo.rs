@@ -0,0 +1,209 @@
+use super::{World, Input};
+use super::mob::player::{Direction, ZoomKind, Projectile};
+use super::save::{self, SaveError};
+
+use macroquad::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the format changes, as older demos cannot be read.
+pub const VERSION: u32 = 2;
+
+/// the input of every tick of a run, to play it back exactly
+///
+/// playback only matches if the world starts from the same level and seed,
+/// and steps by [`super::TICK`]
+#[derive(Serialize, Deserialize)]
+pub struct Demo {
+    pub version: u32,
+    /// Hexadecimal, as TOML integers are signed.
+    seed: String,
+    /// The built-in level is used if absent.
+    pub level: Option<PathBuf>,
+    /// Of the final world snapshot, in hexadecimal.
+    hash: String,
+    ticks: Vec<DemoTick>
+}
+
+/// run-length encoded input
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct DemoTick {
+    #[serde(skip_serializing_if = "is_one")]
+    repeat: u32,
+
+    #[serde(skip_serializing_if = "is_zero")]
+    look: [f32; 2],
+    zoom: Option<ZoomKind>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    moves: Vec<Direction>,
+
+    #[serde(skip_serializing_if = "is_false")]
+    sprint: bool,
+    select: Option<Projectile>,
+    #[serde(skip_serializing_if = "is_false")]
+    charge: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    jump: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    throw: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    pick_up: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    grab: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    hold: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    super_leap: bool,
+    #[serde(skip_serializing_if = "is_false")]
+    respawn: bool
+}
+
+impl Demo {
+    pub fn new(seed: u64, level: Option<PathBuf>) -> Self {
+        Self {
+            version: VERSION,
+            seed: save::to_hex(seed),
+            level,
+            hash: String::new(),
+            ticks: Vec::new()
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        let demo: Self = save::read_versioned(path, VERSION)?;
+
+        demo.seed_checked()?;
+        demo.hash_checked()?;
+
+        Ok(demo)
+    }
+
+    /// should only be called after `finish`
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        save::write_toml(self, path)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed_checked().expect("seed should be validated")
+    }
+
+    pub fn tick_count(&self) -> usize {
+        self.ticks.iter().map(|t| t.repeat as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ticks.is_empty()
+    }
+
+    /// adds the input of the next tick
+    pub fn record(&mut self, input: &Input) {
+        let tick = DemoTick::from(input);
+
+        match self.ticks.last_mut() {
+            Some(last) if last.repeat < u32::MAX
+                && DemoTick { repeat: 1, ..last.clone() } == tick =>
+            {
+                last.repeat += 1;
+            }
+            _ => self.ticks.push(tick)
+        }
+    }
+
+    /// stores the state of `world` after the last tick
+    pub fn finish(&mut self, world: &World) {
+        self.hash = save::to_hex(world.snapshot().hash());
+    }
+
+    /// whether `world` ended up in the recorded state
+    pub fn matches(&self, world: &World) -> bool {
+        self.hash_checked().ok() == Some(world.snapshot().hash())
+    }
+
+    /// the input of every tick, in order
+    pub fn inputs(&self) -> impl Iterator<Item = Input> + '_ {
+        self.ticks.iter().flat_map(|t| {
+            let input = Input::from(t);
+
+            (0..t.repeat).map(move |_| input.clone())
+        })
+    }
+}
+
+impl Demo {
+    fn seed_checked(&self) -> Result<u64, SaveError> {
+        save::parse_hex(&self.seed, "seed")
+    }
+
+    fn hash_checked(&self) -> Result<u64, SaveError> {
+        save::parse_hex(&self.hash, "hash")
+    }
+}
+
+impl Default for DemoTick {
+    fn default() -> Self {
+        Self {
+            repeat: 1,
+            look: [0.0; 2],
+            zoom: None,
+            moves: Vec::new(),
+            sprint: false,
+            select: None,
+            charge: false,
+            jump: false,
+            throw: false,
+            pick_up: false,
+            grab: false,
+            hold: false,
+            super_leap: false,
+            respawn: false
+        }
+    }
+}
+
+impl From<&Input> for DemoTick {
+    fn from(input: &Input) -> Self {
+        Self {
+            repeat: 1,
+            look: input.look.into(),
+            zoom: input.zoom,
+            moves: input.moves.clone(),
+            sprint: input.is_sprinting,
+            select: input.select,
+            charge: input.is_charging,
+            jump: input.jump,
+            throw: input.throw,
+            pick_up: input.pick_up,
+            grab: input.grab,
+            hold: input.is_grabbing,
+            super_leap: input.super_leap,
+            respawn: input.respawn
+        }
+    }
+}
+
+impl From<&DemoTick> for Input {
+    fn from(tick: &DemoTick) -> Self {
+        Self {
+            look: Vec2::from(tick.look),
+            zoom: tick.zoom,
+            moves: tick.moves.clone(),
+            is_sprinting: tick.sprint,
+            select: tick.select,
+            is_charging: tick.charge,
+            jump: tick.jump,
+            throw: tick.throw,
+            pick_up: tick.pick_up,
+            grab: tick.grab,
+            is_grabbing: tick.hold,
+            super_leap: tick.super_leap,
+            respawn: tick.respawn
+        }
+    }
+}
+
+fn is_one(n: &u32) -> bool { *n == 1 }
+fn is_zero(v: &[f32; 2]) -> bool { *v == [0.0; 2] }
+fn is_false(b: &bool) -> bool { !b }
@@ -0,0 +1,216 @@
+use super::Rng;
+use super::collision;
+
+use macroquad::prelude::*;
+
+/// Most samples along each side of generated hills.
+const MAX_HILL_SAMPLES: usize = 257;
+/// Layers of ever smaller bumps added to generated hills.
+const HILL_OCTAVES: u32 = 4;
+/// Where rays go below the ground is narrowed down to this, in meters.
+const RAY_PRECISION: f32 = 0.001;
+
+/// heights of the ground over the square terrain, sampled on a regular grid
+/// and interpolated between samples
+#[derive(Clone)]
+pub struct Heightmap {
+    /// Side of the square covered, centred on the origin.
+    width: f32,
+    /// Samples along x.
+    cols: usize,
+    /// Samples along z.
+    rows: usize,
+    /// Row by row, from the corner at -x and -z.
+    heights: Vec<f32>
+}
+
+impl Heightmap {
+    /// `heights` are given row by row, from the corner at -x and -z
+    ///
+    /// panics unless there are `cols * rows` heights, with at least two
+    /// columns and rows
+    pub fn new(width: f32, cols: usize, rows: usize, heights: Vec<f32>)
+        -> Self
+    {
+        assert!(cols >= 2 && rows >= 2, "a heightmap needs 2 by 2 samples");
+        assert_eq!(heights.len(), cols * rows, "wrong number of heights");
+
+        Self { width, cols, rows, heights }
+    }
+
+    /// rolling hills up to `height`, with bumps about `scale` apart
+    ///
+    /// the same `seed` always gives the same hills
+    pub fn hills(width: f32, seed: u64, height: f32, scale: f32) -> Self {
+        // About a sample per metre.
+        let samples = (width as usize + 1).clamp(2, MAX_HILL_SAMPLES);
+        let spacing = width / (samples - 1) as f32;
+
+        let heights = (0..samples * samples)
+            .map(|i| {
+                let (col, row) = (i % samples, i / samples);
+                let pos = vec2(col as f32, row as f32) * spacing / scale;
+
+                fractal_noise(seed, pos) * height
+            })
+            .collect();
+
+        Self::new(width, samples, samples, heights)
+    }
+
+    pub fn cols(&self) -> usize { self.cols }
+    pub fn rows(&self) -> usize { self.rows }
+
+    /// position of the sample at `col` and `row`
+    pub fn sample(&self, col: usize, row: usize) -> Vec3 {
+        let spacing = self.spacing();
+        let corner = -self.width / 2.0;
+
+        vec3(
+            corner + col as f32 * spacing.x,
+            self.heights[row * self.cols + col],
+            corner + row as f32 * spacing.y
+        )
+    }
+
+    /// height of the ground at `x` and `z`, as at the nearest edge beyond
+    /// the map
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        let spacing = self.spacing();
+        // In samples from the corner.
+        let grid_pos = (vec2(x, z) + self.width / 2.0) / spacing;
+        let last = vec2((self.cols - 1) as f32, (self.rows - 1) as f32);
+        let grid_pos = grid_pos.clamp(Vec2::ZERO, last);
+
+        // Top left of the cell, which must have samples on every side.
+        let cell = grid_pos.floor().min(last - 1.0);
+        let t = grid_pos - cell;
+        let (col, row) = (cell.x as usize, cell.y as usize);
+        let h = |dc: usize, dr: usize| {
+            self.heights[(row + dr) * self.cols + col + dc]
+        };
+
+        let near = lerp(h(0, 0), h(1, 0), t.x);
+        let far = lerp(h(0, 1), h(1, 1), t.x);
+
+        lerp(near, far, t.y)
+    }
+
+    /// distance from `origin` along the unit vector `dir` to where the ray
+    /// first goes below the ground, within `max_dist`
+    ///
+    /// only counts where `is_land` within the square covered, and never hits
+    /// rays starting below the ground
+    pub fn ray_hit<F>(&self, origin: Vec3, dir: Vec3, max_dist: f32, is_land: F)
+        -> Option<f32>
+        where F: Fn(f32, f32) -> bool
+    {
+        // Only the square covered is marched through.
+        let half = self.width / 2.0;
+        let (enter, exit, _) = collision::ray_span(
+            origin, dir,
+            vec3(-half, f32::MIN, -half), vec3(half, f32::MAX, half)
+        )?;
+        let max_dist = max_dist.min(exit);
+
+        let step = self.spacing().min_element() / 2.0;
+        let height_above = |t: f32| {
+            let pos = origin + dir * t;
+
+            pos.y - self.height_at(pos.x, pos.z)
+        };
+
+        let mut t = enter;
+
+        while t < max_dist {
+            let next = (t + step).min(max_dist);
+            let pos = origin + dir * next;
+
+            if height_above(t) > 0.0 && height_above(next) <= 0.0
+                && is_land(pos.x, pos.z)
+            {
+                // Narrow down the crossing.
+                let (mut above, mut below) = (t, next);
+
+                while below - above > RAY_PRECISION {
+                    let mid = (above + below) / 2.0;
+
+                    if height_above(mid) > 0.0 {
+                        above = mid;
+                    } else {
+                        below = mid;
+                    }
+                }
+
+                return Some(below)
+            }
+
+            t = next;
+        }
+
+        None
+    }
+
+    /// upward unit vector perpendicular to the ground at `x` and `z`
+    pub fn normal_at(&self, x: f32, z: f32) -> Vec3 {
+        let spacing = self.spacing();
+        let slope_x = (self.height_at(x + spacing.x, z)
+            - self.height_at(x - spacing.x, z)) / (2.0 * spacing.x);
+        let slope_z = (self.height_at(x, z + spacing.y)
+            - self.height_at(x, z - spacing.y)) / (2.0 * spacing.y);
+
+        vec3(-slope_x, 1.0, -slope_z).normalize()
+    }
+}
+
+impl Heightmap {
+    /// between samples, along x and z
+    fn spacing(&self) -> Vec2 {
+        vec2(
+            self.width / (self.cols - 1) as f32,
+            self.width / (self.rows - 1) as f32
+        )
+    }
+}
+
+/// smooth noise from 0 to 1, with features about 1 apart and smaller
+/// details on top
+fn fractal_noise(seed: u64, pos: Vec2) -> f32 {
+    let (mut sum, mut amplitude, mut total) = (0.0, 1.0, 0.0);
+
+    for octave in 0..HILL_OCTAVES {
+        let frequency = (1 << octave) as f32;
+
+        // Each octave gets its own lattice.
+        sum += value_noise(seed.wrapping_add(octave as u64), pos * frequency)
+            * amplitude;
+        total += amplitude;
+        amplitude /= 2.0;
+    }
+
+    sum / total
+}
+
+/// random values from 0 to 1 at whole coordinates, smoothly interpolated
+fn value_noise(seed: u64, pos: Vec2) -> f32 {
+    let cell = pos.floor();
+    let t = pos - cell;
+    // Smoothstep, so there are no creases along the lattice.
+    let t = t * t * (3.0 - 2.0 * t);
+    let (x, z) = (cell.x as i64, cell.y as i64);
+    let v = |dx: i64, dz: i64| lattice_value(seed, x + dx, z + dz);
+
+    lerp(lerp(v(0, 0), v(1, 0), t.x), lerp(v(0, 1), v(1, 1), t.x), t.y)
+}
+
+fn lattice_value(seed: u64, x: i64, z: i64) -> f32 {
+    // Large odd constants, so that neighbours get unrelated states.
+    let key = (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (z as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+
+    Rng::new(seed ^ key).next_f32()
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
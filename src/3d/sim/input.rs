@@ -0,0 +1,48 @@
+use super::mob::player::{Direction, ZoomKind, Projectile};
+
+use macroquad::prelude::*;
+
+/// actions gathered between ticks
+///
+/// held actions persist until released, while pressed actions are kept until
+/// the next tick consumes them
+#[derive(Clone, Default)]
+pub struct Input {
+    /// Mouse movement since the last tick.
+    pub look: Vec2,
+    pub zoom: Option<ZoomKind>,
+    pub moves: Vec<Direction>,
+    pub is_sprinting: bool,
+    /// Changes what is thrown next.
+    pub select: Option<Projectile>,
+    /// Builds up the next throw. Without it, the charge is lost unless
+    /// thrown.
+    pub is_charging: bool,
+
+    pub jump: bool,
+    /// Throws with whatever charge was built up.
+    pub throw: bool,
+    /// Picks up the resting ball being looked at.
+    pub pick_up: bool,
+    /// Grabs the frog being looked at.
+    pub grab: bool,
+    /// Keeps carrying the grabbed frog.
+    pub is_grabbing: bool,
+    pub super_leap: bool,
+    /// Only applies if the player is dead.
+    pub respawn: bool
+}
+
+impl Input {
+    /// clears actions that only apply to a single tick
+    pub fn consume(&mut self) {
+        self.look = Vec2::ZERO;
+        self.jump = false;
+        self.select = None;
+        self.throw = false;
+        self.pick_up = false;
+        self.grab = false;
+        self.super_leap = false;
+        self.respawn = false;
+    }
+}
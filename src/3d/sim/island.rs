@@ -0,0 +1,108 @@
+use super::Rng;
+
+use macroquad::prelude::*;
+
+use std::f32::consts::TAU;
+
+/// Corners of the outline of each island.
+const CORNERS: usize = 12;
+/// Corners are at least this fraction of the radius from the centre.
+const MIN_CORNER_RADIUS: f32 = 0.6;
+
+/// how a level wants its islands generated
+#[derive(Clone, Copy)]
+pub struct IslandSettings {
+    /// At least this many, more if needed for everything to start on land.
+    pub count: u32,
+    /// Smallest and largest distance from the centre of an island to its
+    /// shore.
+    pub radius: (f32, f32)
+}
+
+/// a piece of land, with an irregular outline around its centre
+#[derive(Clone)]
+pub struct Island {
+    centre: Vec2,
+    /// Corners in order around the centre, in the x and z plane.
+    outline: Vec<Vec2>
+}
+
+impl IslandSettings {
+    /// defaults that suit a terrain of the given width
+    pub fn for_width(width: f32, count: u32) -> Self {
+        Self {
+            count,
+            radius: (width * 0.08, width * 0.2)
+        }
+    }
+
+    /// islands with centres inside the square terrain of side `width`, also
+    /// covering every point of `anchors`
+    ///
+    /// the same `rng` state always gives the same islands
+    pub fn generate(&self, width: f32, anchors: &[Vec3], rng: &mut Rng)
+        -> Vec<Island>
+    {
+        let mut islands: Vec<Island> = Vec::new();
+
+        for a in anchors {
+            let is_covered = islands.iter().any(|i| i.contains(a.x, a.z));
+
+            if !is_covered {
+                islands.push(self.island(vec2(a.x, a.z), rng));
+            }
+        }
+
+        while islands.len() < self.count as usize {
+            let centre = vec2(rng.next_f32(), rng.next_f32()) - 0.5;
+
+            islands.push(self.island(centre * width, rng));
+        }
+
+        islands
+    }
+}
+
+impl IslandSettings {
+    fn island(&self, centre: Vec2, rng: &mut Rng) -> Island {
+        let (min, max) = self.radius;
+        let radius = min + (max - min) * rng.next_f32();
+
+        let outline = (0..CORNERS)
+            .map(|i| {
+                // Jittered, but still in order around the centre.
+                let angle = (i as f32 + rng.next_f32() * 0.5)
+                    * TAU / CORNERS as f32;
+                let dist = radius * (MIN_CORNER_RADIUS
+                    + (1.0 - MIN_CORNER_RADIUS) * rng.next_f32());
+
+                centre + Vec2::from_angle(angle) * dist
+            })
+            .collect();
+
+        Island { centre, outline }
+    }
+}
+
+impl Island {
+    pub fn centre(&self) -> Vec2 { self.centre }
+    pub fn outline(&self) -> &[Vec2] { &self.outline }
+
+    /// whether the point at `x` and `z` is on the island
+    pub fn contains(&self, x: f32, z: f32) -> bool {
+        let mut is_inside = false;
+
+        // Count the edges crossed by a ray going towards +x.
+        for (i, &a) in self.outline.iter().enumerate() {
+            let b = self.outline[(i + 1) % self.outline.len()];
+
+            if (a.y > z) != (b.y > z) {
+                let crossing_x = a.x + (z - a.y) / (b.y - a.y) * (b.x - a.x);
+
+                if x < crossing_x { is_inside = !is_inside }
+            }
+        }
+
+        is_inside
+    }
+}
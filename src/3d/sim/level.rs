@@ -0,0 +1,473 @@
+use super::terrain::WallSettings;
+use super::heightmap::Heightmap;
+use super::island::IslandSettings;
+use super::mob::frog;
+use super::mob::flock::Flock;
+use super::rules::Rule;
+use super::zone::Zone;
+use super::solid::Solid;
+
+use macroquad::prelude::*;
+
+use serde::Deserialize;
+
+use std::{fmt, fs, io};
+use std::path::{Path, PathBuf};
+
+/// Used when the level does not say.
+const DEFAULT_LIVES: u32 = 3;
+/// Used when the level does not say, in meters.
+const DEFAULT_VOID_DEPTH: f32 = 100.0;
+
+/// The level used when none is given.
+const DEFAULT: &str = include_str!("../../../assets/levels/default.toml");
+
+/// a validated puzzle description
+#[derive(Clone)]
+pub struct Level {
+    /// Side of the square terrain.
+    pub width: f32,
+    /// Flat ground at a height of 0 if none.
+    pub heightmap: Option<Heightmap>,
+    /// Generated anew for every run, instead of the square ground.
+    pub islands: Option<IslandSettings>,
+    pub wall: WallSettings,
+    /// Where the feet of the player start.
+    pub spawn: Vec3,
+    pub frogs: Vec<FrogSpawn>,
+    /// How frogs steer relative to each other, if they flock.
+    pub flock: Option<Flock>,
+    pub win: Rule,
+    /// Platforms, ramps and walls.
+    pub solids: Vec<Solid>,
+
+    /// Balls the player starts with, unlimited if none.
+    pub ammo: Option<u32>,
+    /// Deaths allowed before the game is over.
+    pub lives: u32,
+    /// How far below the ground the player dies, in meters.
+    pub void_depth: f32,
+    /// Kill the player on contact.
+    pub hazards: Vec<Zone>,
+    /// Move the respawn point on contact.
+    pub checkpoints: Vec<Zone>
+}
+
+#[derive(Clone, Copy)]
+pub struct FrogSpawn {
+    pub pos: Vec3,
+    pub color: Color,
+    /// In kilograms.
+    pub mass: f32,
+    pub restitution: f32,
+    /// Never moves on its own.
+    pub is_calm: bool
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(io::Error),
+    Syntax(toml::de::Error),
+    Invalid(String)
+}
+
+impl Level {
+    /// files the level refers to are relative to its directory
+    pub fn load(path: &Path) -> Result<Self, LevelError> {
+        let text = fs::read_to_string(path).map_err(LevelError::Io)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+
+        Self::parse_in(&text, dir)
+    }
+
+    /// files the level refers to are relative to the working directory
+    pub fn parse(text: &str) -> Result<Self, LevelError> {
+        Self::parse_in(text, Path::new(""))
+    }
+}
+
+impl Level {
+    fn parse_in(text: &str, dir: &Path) -> Result<Self, LevelError> {
+        let file: LevelFile = toml::from_str(text)
+            .map_err(LevelError::Syntax)?;
+
+        file.validate(dir)
+    }
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self::parse(DEFAULT).expect("default level should be valid")
+    }
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "cannot read level: {e}"),
+            Self::Syntax(e) => write!(f, "malformed level: {e}"),
+            Self::Invalid(msg) => write!(f, "invalid level: {msg}")
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+/// level as written on disk, before validation
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LevelFile {
+    #[serde(default)]
+    spawn: [f32; 3],
+    win: Rule,
+    lives: Option<u32>,
+    ammo: Option<u32>,
+    void_depth: Option<f32>,
+    terrain: TerrainDef,
+    #[serde(default)]
+    wall: WallDef,
+    frogs: Vec<FrogDef>,
+    flock: Option<FlockDef>,
+    #[serde(default)]
+    solids: Vec<Solid>,
+    #[serde(default)]
+    hazards: Vec<Zone>,
+    #[serde(default)]
+    checkpoints: Vec<Zone>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TerrainDef {
+    width: f32,
+    heightmap: Option<HeightmapDef>,
+    hills: Option<HillsDef>,
+    islands: Option<IslandsDef>
+}
+
+/// a grayscale image stretched over the terrain, with north (-z) at the top
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HeightmapDef {
+    image: PathBuf,
+    /// Of white pixels, black ones being at 0.
+    height: f32
+}
+
+/// generated hills, the same every time for the same seed
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HillsDef {
+    #[serde(default)]
+    seed: u64,
+    height: f32,
+    /// Rough distance between hilltops.
+    scale: f32
+}
+
+/// the radius is derived from the terrain width if missing
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IslandsDef {
+    count: u32,
+    radius: Option<(f32, f32)>
+}
+
+/// missing fields are derived from the terrain width
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct WallDef {
+    size: Option<f32>,
+    shake: Option<f32>,
+    stretch: Option<f32>,
+    max_offset: Option<f32>,
+    /// Relative to the size.
+    stretch_limit: Option<(f32, f32)>
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FrogDef {
+    pos: [f32; 3],
+    color: ColorDef,
+    mass: Option<f32>,
+    restitution: Option<f32>,
+    #[serde(default)]
+    calm: bool
+}
+
+/// missing fields take default values
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FlockDef {
+    radius: Option<f32>,
+    cohesion: Option<f32>,
+    separation: Option<f32>,
+    alignment: Option<f32>,
+    scatter_time: Option<f32>
+}
+
+/// either a name like "violet" or RGB(A) components from 0 to 255
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorDef {
+    Name(String),
+    Rgb([u8; 3]),
+    Rgba([u8; 4])
+}
+
+impl LevelFile {
+    fn validate(self, dir: &Path) -> Result<Level, LevelError> {
+        let width = self.terrain.width;
+
+        check(width.is_finite() && width > 0.0,
+              "terrain width must be positive")?;
+
+        let heightmap = self.terrain.heightmap(dir)?;
+        let islands = self.terrain.islands.as_ref()
+            .map(|i| i.validate(width))
+            .transpose()?;
+
+        let spawn = Vec3::from(self.spawn);
+
+        check(spawn.is_finite(), "spawn must be finite")?;
+        check(spawn.y >= 0.0 && spawn.x.abs() <= width / 2.0
+              && spawn.z.abs() <= width / 2.0,
+              "spawn must be above the terrain")?;
+
+        check(!self.frogs.is_empty(), "there must be at least one frog")?;
+
+        let lives = self.lives.unwrap_or(DEFAULT_LIVES);
+
+        check(lives > 0, "there must be at least one life")?;
+
+        let void_depth = self.void_depth.unwrap_or(DEFAULT_VOID_DEPTH);
+
+        check(void_depth.is_finite() && void_depth > 0.0,
+              "void depth must be positive")?;
+
+        self.win.validate(self.frogs.len())
+            .map_err(|msg| LevelError::Invalid(format!("win: {msg}")))?;
+
+        let frogs = self.frogs.into_iter()
+            .enumerate()
+            .map(|(i, f)| f.validate().map_err(|e| match e {
+                LevelError::Invalid(msg) => {
+                    LevelError::Invalid(format!("frog {}: {msg}", i + 1))
+                }
+                e => e
+            }))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Level {
+            width,
+            heightmap,
+            islands,
+            wall: self.wall.validate(width)?,
+            spawn,
+            frogs,
+            flock: self.flock.map(FlockDef::validate).transpose()?,
+            win: self.win,
+            solids: self.solids,
+
+            ammo: self.ammo,
+            lives,
+            void_depth,
+            hazards: self.hazards,
+            checkpoints: self.checkpoints
+        })
+    }
+}
+
+impl TerrainDef {
+    fn heightmap(&self, dir: &Path) -> Result<Option<Heightmap>, LevelError> {
+        match (&self.heightmap, &self.hills) {
+            (None, None) => Ok(None),
+            (Some(map), None) => map.validate(self.width, dir).map(Some),
+            (None, Some(hills)) => hills.validate(self.width).map(Some),
+            (Some(_), Some(_)) => Err(LevelError::Invalid(
+                "terrain cannot have both a heightmap and hills".to_string()
+            ))
+        }
+    }
+}
+
+impl HeightmapDef {
+    fn validate(&self, width: f32, dir: &Path)
+        -> Result<Heightmap, LevelError>
+    {
+        check(self.height.is_finite() && self.height >= 0.0,
+              "heightmap height must not be negative")?;
+
+        let path = dir.join(&self.image);
+        let image = image::open(&path)
+            .map_err(|e| {
+                let msg = format!("heightmap {}: {e}", path.display());
+
+                LevelError::Invalid(msg)
+            })?
+            .into_luma8();
+        let (cols, rows) = (image.width() as usize, image.height() as usize);
+
+        check(cols >= 2 && rows >= 2,
+              "heightmap image must be at least 2 by 2 pixels")?;
+
+        let heights = image.pixels()
+            .map(|p| p.0[0] as f32 / 255.0 * self.height)
+            .collect();
+
+        Ok(Heightmap::new(width, cols, rows, heights))
+    }
+}
+
+impl HillsDef {
+    fn validate(&self, width: f32) -> Result<Heightmap, LevelError> {
+        check(self.height.is_finite() && self.height >= 0.0,
+              "hills height must not be negative")?;
+        check(self.scale.is_finite() && self.scale > 0.0,
+              "hills scale must be positive")?;
+
+        Ok(Heightmap::hills(width, self.seed, self.height, self.scale))
+    }
+}
+
+impl IslandsDef {
+    fn validate(&self, width: f32) -> Result<IslandSettings, LevelError> {
+        let default = IslandSettings::for_width(width, self.count);
+        let radius = self.radius.unwrap_or(default.radius);
+
+        check(self.count > 0, "there must be at least one island")?;
+        check(radius.0.is_finite() && radius.0 > 0.0 && radius.0 <= radius.1
+              && radius.1.is_finite(),
+              "islands radius must be positive and in increasing order")?;
+
+        Ok(IslandSettings { radius, ..default })
+    }
+}
+
+impl WallDef {
+    fn validate(self, width: f32) -> Result<WallSettings, LevelError> {
+        let default = WallSettings::for_width(width);
+        let size = self.size.unwrap_or(default.size);
+        let dim_limit = match self.stretch_limit {
+            Some((min, max)) => (size * min, size * max),
+            None => default.dim_limit
+        };
+
+        let wall = WallSettings {
+            size,
+            shake_coeff: self.shake.unwrap_or(default.shake_coeff),
+            stretch_coeff: self.stretch.unwrap_or(default.stretch_coeff),
+            max_offset: self.max_offset.unwrap_or(default.max_offset),
+            dim_limit
+        };
+
+        check(size.is_finite() && size > 0.0, "wall size must be positive")?;
+        check(wall.shake_coeff >= 0.0, "wall shake must not be negative")?;
+        check(wall.stretch_coeff >= 0.0, "wall stretch must not be negative")?;
+        check(wall.max_offset >= 0.0,
+              "wall max_offset must not be negative")?;
+        check(dim_limit.0 > 0.0 && dim_limit.0 <= dim_limit.1,
+              "wall stretch_limit must be positive and in increasing order")?;
+
+        Ok(wall)
+    }
+}
+
+impl FrogDef {
+    fn validate(self) -> Result<FrogSpawn, LevelError> {
+        let pos = Vec3::from(self.pos);
+
+        let mass = self.mass.unwrap_or(frog::DEFAULT_MASS);
+        let restitution = self.restitution
+            .unwrap_or(frog::DEFAULT_RESTITUTION);
+
+        check(pos.is_finite(), "position must be finite")?;
+        check(mass.is_finite() && mass > 0.0, "mass must be positive")?;
+        check((0.0..=1.0).contains(&restitution),
+              "restitution must be between 0 and 1")?;
+
+        Ok(FrogSpawn {
+            pos,
+            color: self.color.validate()?,
+            mass,
+            restitution,
+            is_calm: self.calm
+        })
+    }
+}
+
+impl FlockDef {
+    fn validate(self) -> Result<Flock, LevelError> {
+        let default = Flock::default();
+        let flock = Flock {
+            radius: self.radius.unwrap_or(default.radius),
+            cohesion: self.cohesion.unwrap_or(default.cohesion),
+            separation: self.separation.unwrap_or(default.separation),
+            alignment: self.alignment.unwrap_or(default.alignment),
+            scatter_time: self.scatter_time.unwrap_or(default.scatter_time)
+        };
+        let weights = [flock.cohesion, flock.separation, flock.alignment];
+
+        check(flock.radius.is_finite() && flock.radius > 0.0,
+              "flock radius must be positive")?;
+        check(weights.iter().all(|w| w.is_finite() && *w >= 0.0),
+              "flock weights must not be negative")?;
+        check(flock.scatter_time.is_finite() && flock.scatter_time >= 0.0,
+              "flock scatter_time must not be negative")?;
+
+        Ok(flock)
+    }
+}
+
+impl ColorDef {
+    fn validate(self) -> Result<Color, LevelError> {
+        match self {
+            Self::Name(name) => color_from_name(&name).ok_or_else(|| {
+                LevelError::Invalid(format!("unknown color \"{name}\""))
+            }),
+            Self::Rgb([r, g, b]) => Ok(Color::from_rgba(r, g, b, 255)),
+            Self::Rgba([r, g, b, a]) => Ok(Color::from_rgba(r, g, b, a))
+        }
+    }
+}
+
+fn check(cond: bool, msg: &str) -> Result<(), LevelError> {
+    if cond {
+        Ok(())
+    } else {
+        Err(LevelError::Invalid(msg.to_string()))
+    }
+}
+
+fn color_from_name(name: &str) -> Option<Color> {
+    let color = match name {
+        "lightgray" => LIGHTGRAY,
+        "gray" => GRAY,
+        "darkgray" => DARKGRAY,
+        "yellow" => YELLOW,
+        "gold" => GOLD,
+        "orange" => ORANGE,
+        "pink" => PINK,
+        "red" => RED,
+        "maroon" => MAROON,
+        "green" => GREEN,
+        "lime" => LIME,
+        "darkgreen" => DARKGREEN,
+        "skyblue" => SKYBLUE,
+        "blue" => BLUE,
+        "darkblue" => DARKBLUE,
+        "purple" => PURPLE,
+        "violet" => VIOLET,
+        "darkpurple" => DARKPURPLE,
+        "beige" => BEIGE,
+        "brown" => BROWN,
+        "darkbrown" => DARKBROWN,
+        "white" => WHITE,
+        "black" => BLACK,
+        "magenta" => MAGENTA,
+        _ => return None
+    };
+
+    Some(color)
+}
@@ -0,0 +1,202 @@
+use super::Mob;
+use super::frog::Frog;
+
+use crate::sim::terrain::{self, Terrain};
+use crate::sim::save::BallSnapshot;
+
+use macroquad::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+const DIM: Vec3 = vec3(0.25, 0.25, 0.25);
+
+/// In kilograms, about a tennis ball.
+pub const DEFAULT_MASS: f32 = 0.15;
+pub const DEFAULT_RESTITUTION: f32 = 0.6;
+/// In kilograms, about a cricket ball filled with lead.
+const HEAVY_MASS: f32 = 0.6;
+
+/// Rolling resistance, far lower than the friction of sliding mobs.
+const FRICTION: f32 = 1.0;
+/// Slower impacts with the ground, in meters per second, do not bounce.
+const MIN_BOUNCE_SPEED: f32 = 1.0;
+/// Slower balls on the ground, in meters per second, stop.
+const REST_SPEED: f32 = 0.1;
+/// Seconds before a ball disappears.
+const LIFETIME: f32 = 60.0;
+
+pub struct Ball {
+    pos: Vec3,
+    prev_pos: Vec3,
+    vel: Vec3,
+    mass: f32,
+    restitution: f32,
+    kind: Kind,
+    /// Seconds since it was thrown.
+    age: f32,
+    /// Index of the frog it is glued to, and its offset from that frog.
+    stuck_to: Option<(usize, Vec3)>
+}
+
+/// what a ball is made of, which changes how it moves and strikes frogs
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+    #[default]
+    Regular,
+    /// Glues itself to the first frog it strikes.
+    Sticky,
+    /// Hardly slows down when bouncing or rolling.
+    Bouncy,
+    /// Knocks frogs much further.
+    Heavy
+}
+
+impl Ball {
+    pub fn new(pos: Vec3, vel: Vec3, kind: Kind) -> Self {
+        Self {
+            pos, prev_pos: pos, vel,
+            mass: kind.mass(),
+            restitution: kind.restitution(),
+            kind,
+            age: 0.0,
+            stuck_to: None
+        }
+    }
+
+    pub fn kind(&self) -> Kind { self.kind }
+
+    /// index of the frog the ball is glued to, if any
+    pub fn stuck_to(&self) -> Option<usize> {
+        self.stuck_to.map(|(i, _)| i)
+    }
+
+    /// lying still on the ground
+    pub fn is_resting(&self) -> bool {
+        self.vel == Vec3::ZERO && self.stuck_to.is_none()
+    }
+
+    /// too old, or deep in the void, and should be removed
+    pub fn is_expired(&self) -> bool {
+        self.age > LIFETIME || self.pos.y < terrain::VOID_END
+    }
+
+    pub fn snapshot(&self) -> BallSnapshot {
+        BallSnapshot {
+            pos: self.pos.into(),
+            prev_pos: self.prev_pos.into(),
+            vel: self.vel.into(),
+            mass: self.mass,
+            restitution: self.restitution,
+            kind: self.kind,
+            age: self.age,
+            stuck_to: self.stuck_to.map(|(i, _)| i),
+            stuck_offset: self.stuck_to.map_or([0.0; 3], |(_, o)| o.into())
+        }
+    }
+
+    pub fn restore(snap: &BallSnapshot) -> Self {
+        Self {
+            pos: snap.pos.into(),
+            prev_pos: snap.prev_pos.into(),
+            vel: snap.vel.into(),
+            mass: snap.mass,
+            restitution: snap.restitution,
+            kind: snap.kind,
+            age: snap.age,
+            stuck_to: snap.stuck_to.map(|i| (i, snap.stuck_offset.into()))
+        }
+    }
+
+    /// `frogs` are those it may be glued to
+    pub fn update(
+        &mut self,
+        terrain: &Terrain,
+        frogs: &[Frog],
+        time_delta: f32
+    ) {
+        self.prev_pos = self.pos;
+        self.age += time_delta;
+
+        if let Some((i, offset)) = self.stuck_to {
+            self.pos = frogs[i].pos() + offset;
+            self.vel = frogs[i].vel();
+
+            return
+        }
+
+        self.apply_forces(terrain, time_delta);
+        self.apply_vel(terrain, time_delta);
+
+        if self.is_on_ground(terrain) { self.bounce(terrain) }
+    }
+
+    /// after bouncing off the frog at `index`
+    pub fn strike(&mut self, index: usize, frog: &Frog) {
+        if self.kind == Kind::Sticky && self.stuck_to.is_none() {
+            self.stuck_to = Some((index, self.pos - frog.pos()));
+            self.vel = frog.vel();
+        }
+    }
+}
+
+impl Ball {
+    /// off the ground if it hit it hard enough, or comes to rest if slow
+    /// enough
+    fn bounce(&mut self, terrain: &Terrain) {
+        let normal = terrain.ground_normal(self.pos, DIM);
+        let impact_speed = -self.vel.dot(normal);
+
+        if impact_speed > MIN_BOUNCE_SPEED {
+            self.vel += normal * impact_speed * (1.0 + self.restitution);
+            return
+        }
+
+        // Rolls along the ground.
+        self.vel += normal * impact_speed.max(0.0);
+
+        if self.vel.length() < REST_SPEED { self.vel = Vec3::ZERO }
+    }
+}
+
+impl Mob for Ball {
+    fn pos(&self) -> Vec3 { self.pos }
+    fn prev_pos(&self) -> Vec3 { self.prev_pos }
+    fn vel(&self) -> Vec3 { self.vel }
+    // TODO: average dimensions of small ball
+    fn dim(&self) -> Vec3 { DIM }
+    fn mass(&self) -> f32 { self.mass }
+    fn restitution(&self) -> f32 { self.restitution }
+    fn friction(&self) -> f32 { self.kind.friction() }
+
+    fn set_pos(&mut self, val: Vec3) { self.pos = val }
+    fn set_vel(&mut self, val: Vec3) { self.vel = val }
+}
+
+impl Kind {
+    fn mass(self) -> f32 {
+        match self {
+            Self::Heavy => HEAVY_MASS,
+            _ => DEFAULT_MASS
+        }
+    }
+
+    fn restitution(self) -> f32 {
+        match self {
+            Self::Regular => DEFAULT_RESTITUTION,
+            Self::Sticky => 0.0,
+            Self::Bouncy => 0.95,
+            Self::Heavy => 0.3
+        }
+    }
+
+    fn friction(self) -> f32 {
+        match self {
+            Self::Regular | Self::Heavy => FRICTION,
+            // Clings to the ground, like other mobs.
+            Self::Sticky => super::FRICTION,
+            Self::Bouncy => FRICTION / 4.0
+        }
+    }
+}
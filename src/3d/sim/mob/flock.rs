@@ -0,0 +1,80 @@
+use macroquad::prelude::*;
+
+/// how frogs of a level steer relative to each other when hopping around,
+/// like boids
+///
+/// weights are relative to a random hop, which weighs 1
+#[derive(Clone, Copy)]
+pub struct Flock {
+    /// Frogs only notice others this close, in meters.
+    pub radius: f32,
+    /// Towards the centre of nearby frogs.
+    pub cohesion: f32,
+    /// Away from nearby frogs, more strongly the closer they are.
+    pub separation: f32,
+    /// Along the average velocity of nearby frogs.
+    pub alignment: f32,
+    /// Seconds during which frogs struck by a ball avoid the others.
+    pub scatter_time: f32
+}
+
+impl Flock {
+    /// horizontal direction, weighted, that a frog at `centre` is drawn to
+    ///
+    /// `frogs` are the centres and velocities of every frog. scattering
+    /// frogs are repelled by the others instead of drawn to them
+    pub fn steer(
+        &self,
+        centre: Vec3,
+        frogs: &[(Vec3, Vec3)],
+        is_scattering: bool
+    ) -> Vec3 {
+        let flat = vec3(1.0, 0.0, 1.0);
+        let mut count = 0;
+        let (mut centre_sum, mut vel_sum) = (Vec3::ZERO, Vec3::ZERO);
+        let mut away = Vec3::ZERO;
+
+        for &(other, vel) in frogs {
+            let offset = (other - centre) * flat;
+            let dist_sq = offset.length_squared();
+
+            // The frog itself is at a distance of 0.
+            if dist_sq == 0.0 || dist_sq > self.radius * self.radius {
+                continue
+            }
+
+            count += 1;
+            centre_sum += other;
+            vel_sum += vel;
+            away -= offset / dist_sq;
+        }
+
+        if count == 0 { return Vec3::ZERO }
+
+        let count = count as f32;
+        let to_centre = ((centre_sum / count - centre) * flat)
+            .normalize_or_zero();
+        let heading = (vel_sum / count * flat).normalize_or_zero();
+        let cohesion = if is_scattering {
+            -self.cohesion
+        } else {
+            self.cohesion
+        };
+
+        to_centre * cohesion
+            + away * self.separation
+            + heading * self.alignment
+    }
+}
+
+impl Default for Flock {
+    fn default() -> Self {
+        Self {
+            radius: 8.0,
+            cohesion: 1.0,
+            separation: 1.0,
+            alignment: 0.5,
+            scatter_time: 5.0
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use super::{Mob, GRAVITY};
+use super::flock::Flock;
+
+use crate::sim::Rng;
+use crate::sim::terrain::Terrain;
+use crate::sim::level::FrogSpawn;
+use crate::sim::save::FrogSnapshot;
+
+use macroquad::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+use std::f32::consts::TAU;
+
+const DIM: Vec3 = Vec3::splat(1.0);
+
+/// Used when the level does not say, in kilograms.
+pub const DEFAULT_MASS: f32 = 1.0;
+pub const DEFAULT_RESTITUTION: f32 = 0.4;
+
+// In seconds.
+const IDLE_MIN: f32 = 1.0;
+const IDLE_MAX: f32 = 4.0;
+const FLEE_COOLDOWN: f32 = 0.3;
+
+// In meters per second.
+const HOP_SPEED: f32 = 4.0;
+const HOP_UP_SPEED: f32 = 6.0;
+const FLEE_SPEED: f32 = 7.0;
+
+/// Frogs flee from anything closer, in meters.
+const FLEE_RADIUS: f32 = 5.0;
+/// Chance per second of croaking while sitting still.
+const CROAK_RATE: f32 = 0.05;
+/// Directions tried when a hop would land in the void.
+const HOP_TRIES: u32 = 8;
+/// Per second, how quickly a carried frog catches up with where it is held.
+const CARRY_PULL: f32 = 15.0;
+
+pub struct Frog {
+    pos: Vec3,
+    prev_pos: Vec3,
+    vel: Vec3,
+    color: Color,
+    mass: f32,
+    restitution: f32,
+
+    /// Calm frogs never move on their own.
+    is_calm: bool,
+    state: State,
+    /// Seconds until the next hop.
+    timer: f32,
+    /// Seconds left avoiding the flock.
+    scatter_timer: f32
+}
+
+/// what a frog notices around it
+#[derive(Clone, Copy)]
+pub struct Senses<'a> {
+    /// Centres of whatever frogs flee from.
+    pub threats: &'a [Vec3],
+    /// Centres and velocities of every frog, if they flock.
+    pub frogs: &'a [(Vec3, Vec3)],
+    pub flock: Option<&'a Flock>
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    /// Sitting until the timer runs out.
+    Idle,
+    /// In the air after a hop.
+    Hopping,
+    /// Hopping away from a threat as often as possible.
+    Fleeing
+}
+
+impl Frog {
+    pub fn new(spawn: &FrogSpawn) -> Self {
+        Self {
+            pos: spawn.pos,
+            prev_pos: spawn.pos,
+            vel: Vec3::ZERO,
+            color: spawn.color,
+            mass: spawn.mass,
+            restitution: spawn.restitution,
+
+            is_calm: spawn.is_calm,
+            state: State::Idle,
+            timer: IDLE_MAX,
+            scatter_timer: 0.0
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn snapshot(&self) -> FrogSnapshot {
+        FrogSnapshot {
+            pos: self.pos.into(),
+            prev_pos: self.prev_pos.into(),
+            vel: self.vel.into(),
+            color: self.color.into(),
+            mass: self.mass,
+            restitution: self.restitution,
+
+            is_calm: self.is_calm,
+            state: self.state,
+            timer: self.timer,
+            scatter_timer: self.scatter_timer
+        }
+    }
+
+    pub fn restore(snap: &FrogSnapshot) -> Self {
+        Self {
+            pos: snap.pos.into(),
+            prev_pos: snap.prev_pos.into(),
+            vel: snap.vel.into(),
+            color: snap.color.into(),
+            mass: snap.mass,
+            restitution: snap.restitution,
+
+            is_calm: snap.is_calm,
+            state: snap.state,
+            timer: snap.timer,
+            scatter_timer: snap.scatter_timer
+        }
+    }
+
+    /// makes the frog avoid its flock for a while
+    pub fn scatter(&mut self, time: f32) {
+        self.scatter_timer = time;
+    }
+
+    /// pulls the centre of the frog towards `target`, as if held, instead of
+    /// letting it think or fall
+    pub fn carry(
+        &mut self,
+        terrain: &Terrain,
+        target: Vec3,
+        time_delta: f32
+    ) {
+        self.prev_pos = self.pos;
+        self.vel = (target - self.centre()) * CARRY_PULL;
+        self.apply_vel(terrain, time_delta);
+    }
+
+    /// returns whether the frog croaked
+    pub fn update(
+        &mut self,
+        terrain: &Terrain,
+        senses: Senses,
+        rng: &mut Rng,
+        time_delta: f32
+    ) -> bool {
+        let has_croaked = !self.is_calm
+            && self.think(terrain, senses, rng, time_delta);
+
+        self.prev_pos = self.pos;
+        self.apply_forces(terrain, time_delta);
+        self.apply_vel(terrain, time_delta);
+
+        has_croaked
+    }
+}
+
+impl Frog {
+    /// moves on to the next state, and hops if it is time to
+    ///
+    /// returns whether the frog croaked
+    fn think(
+        &mut self,
+        terrain: &Terrain,
+        senses: Senses,
+        rng: &mut Rng,
+        time_delta: f32
+    ) -> bool {
+        self.timer -= time_delta;
+        self.scatter_timer = (self.scatter_timer - time_delta).max(0.0);
+
+        // Nothing to decide in the air.
+        if !self.is_on_ground(terrain) { return false }
+
+        let threat = self.nearest_threat(terrain, senses.threats);
+
+        self.state = match (self.state, threat) {
+            (State::Fleeing, Some(_)) => State::Fleeing,
+            // Flee at once.
+            (State::Idle | State::Hopping, Some(_)) => {
+                self.timer = 0.0;
+
+                State::Fleeing
+            }
+            (State::Idle, None) => State::Idle,
+            // Landed, or the threat is gone.
+            (State::Hopping | State::Fleeing, None) => {
+                self.timer = IDLE_MIN + (IDLE_MAX - IDLE_MIN) * rng.next_f32();
+
+                State::Idle
+            }
+        };
+
+        match (self.state, threat) {
+            (State::Fleeing, Some(threat)) if self.timer <= 0.0 => {
+                let away = (self.centre() - threat) * vec3(1.0, 0.0, 1.0);
+                let away = away.try_normalize().unwrap_or(Vec3::X);
+
+                self.hop(terrain, away, FLEE_SPEED, rng);
+                self.timer = FLEE_COOLDOWN;
+            }
+            (State::Idle, _) if self.timer <= 0.0 => {
+                let angle = rng.next_f32() * TAU;
+                let wander = vec3(angle.cos(), 0.0, angle.sin());
+                let steer = senses.flock.map_or(Vec3::ZERO, |f| {
+                    let is_scattering = self.scatter_timer > 0.0;
+
+                    f.steer(self.centre(), senses.frogs, is_scattering)
+                });
+                let dir = (wander + steer).try_normalize().unwrap_or(wander);
+
+                self.hop(terrain, dir, HOP_SPEED, rng);
+                self.state = State::Hopping;
+            }
+            (State::Idle, _) => return rng.chance(CROAK_RATE * time_delta),
+            _ => ()
+        }
+
+        false
+    }
+
+    /// jumps towards `dir`, or the nearest direction that lands on the
+    /// ground if it leads into the void, if any
+    fn hop(
+        &mut self,
+        terrain: &Terrain,
+        dir: Vec3,
+        speed: f32,
+        rng: &mut Rng
+    ) {
+        // Where the frog would land, ignoring air resistance.
+        let flight_time = 2.0 * HOP_UP_SPEED / GRAVITY;
+        let reach = speed * flight_time + DIM.x;
+        // Turn either way first, at random.
+        let turn = rng.sign(1.0) * TAU / HOP_TRIES as f32;
+
+        let safe_dir = (0..HOP_TRIES)
+            .map(|i| {
+                // Alternate sides: 0, 1, -1, 2, -2...
+                let steps = i.div_ceil(2) as f32;
+                let side = if i % 2 == 0 { -1.0 } else { 1.0 };
+
+                Quat::from_rotation_y(turn * steps * side) * dir
+            })
+            .find(|d| {
+                let landing = self.centre() + *d * reach;
+
+                terrain.is_land(landing.x, landing.z)
+            });
+
+        if let Some(d) = safe_dir {
+            self.vel += d * speed + Vec3::Y * HOP_UP_SPEED;
+        }
+    }
+
+    /// within `FLEE_RADIUS`, and not hidden behind the terrain
+    fn nearest_threat(&self, terrain: &Terrain, threats: &[Vec3])
+        -> Option<Vec3>
+    {
+        let centre = self.centre();
+
+        threats.iter()
+            .copied()
+            .map(|t| (t, t.distance_squared(centre)))
+            .filter(|&(_, dist_sq)| dist_sq < FLEE_RADIUS * FLEE_RADIUS)
+            .filter(|&(t, _)| terrain.has_line_of_sight(centre, t))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(t, _)| t)
+    }
+}
+
+impl Mob for Frog {
+    fn pos(&self) -> Vec3 { self.pos }
+    fn prev_pos(&self) -> Vec3 { self.prev_pos }
+    fn vel(&self) -> Vec3 { self.vel }
+    fn dim(&self) -> Vec3 { DIM }
+    fn mass(&self) -> f32 { self.mass }
+    fn restitution(&self) -> f32 { self.restitution }
+
+    fn set_pos(&mut self, val: Vec3) { self.pos = val }
+    fn set_vel(&mut self, val: Vec3) { self.vel = val }
+}
@@ -0,0 +1,125 @@
+use super::Mob;
+
+use crate::sim::save::GustSnapshot;
+
+use macroquad::prelude::*;
+
+const DIM: Vec3 = vec3(0.5, 0.5, 0.5);
+
+/// In meters per second.
+const SPEED: f32 = 25.0;
+/// Seconds before it dies down.
+const LIFETIME: f32 = 0.4;
+/// Between the axis and the side of the cone, in radians.
+const HALF_ANGLE: f32 = 0.35;
+/// In newtons per square meter of a mob's side, when fully charged.
+const MAX_PRESSURE: f32 = 150.0;
+/// Fraction of the strongest force, when not charged at all.
+const MIN_STRENGTH: f32 = 0.25;
+/// Upwards, as a fraction of the push, so frogs leave the ground.
+const LIFT: f32 = 0.3;
+
+/// a cone of wind blowing away from where it was thrown, pushing frogs and
+/// balls as its front sweeps past them
+///
+/// it goes through everything, and only moves as a mob so it can be drawn
+pub struct Gust {
+    /// Apex of the cone.
+    origin: Vec3,
+    pos: Vec3,
+    prev_pos: Vec3,
+    vel: Vec3,
+    /// Charge it was thrown with, from 0 to 1.
+    strength: f32,
+    /// Seconds since it was thrown.
+    age: f32
+}
+
+impl Gust {
+    /// blowing from `origin` along the unit vector `dir`
+    pub fn new(origin: Vec3, dir: Vec3, charge: f32) -> Self {
+        let pos = origin - DIM / 2.0;
+
+        Self {
+            origin, pos, prev_pos: pos, vel: dir * SPEED,
+            strength: MIN_STRENGTH + (1.0 - MIN_STRENGTH) * charge,
+            age: 0.0
+        }
+    }
+
+    pub fn origin(&self) -> Vec3 { self.origin }
+
+    /// distance from the apex to the front of the cone
+    pub fn reach(&self) -> f32 {
+        (self.centre() - self.origin).length()
+    }
+
+    /// radius of the cone at `dist` from the apex
+    pub fn radius_at(&self, dist: f32) -> f32 {
+        dist * HALF_ANGLE.tan()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.age > LIFETIME
+    }
+
+    pub fn snapshot(&self) -> GustSnapshot {
+        GustSnapshot {
+            origin: self.origin.into(),
+            pos: self.pos.into(),
+            prev_pos: self.prev_pos.into(),
+            vel: self.vel.into(),
+            strength: self.strength,
+            age: self.age
+        }
+    }
+
+    pub fn restore(snap: &GustSnapshot) -> Self {
+        Self {
+            origin: snap.origin.into(),
+            pos: snap.pos.into(),
+            prev_pos: snap.prev_pos.into(),
+            vel: snap.vel.into(),
+            strength: snap.strength,
+            age: snap.age
+        }
+    }
+
+    pub fn update(&mut self, time_delta: f32) {
+        self.prev_pos = self.pos;
+        self.pos += self.vel * time_delta;
+        self.age += time_delta;
+    }
+
+    /// pushes `mob` away from the apex if its centre is inside the cone,
+    /// larger and lighter mobs further
+    pub fn blow<M: Mob>(&self, mob: &mut M, time_delta: f32) {
+        let offset = mob.centre() - self.origin;
+        let dist = offset.length();
+        let axis = self.vel.normalize();
+
+        let is_inside = dist > 0.0 && dist <= self.reach()
+            && offset.dot(axis) >= dist * HALF_ANGLE.cos();
+
+        if !is_inside { return }
+
+        let dim = mob.dim();
+        let dir = (offset / dist + Vec3::Y * LIFT).normalize();
+        let force = dir * MAX_PRESSURE * self.strength * dim.x * dim.y;
+
+        mob.set_vel(mob.vel() + force / mob.mass() * time_delta);
+    }
+}
+
+impl Mob for Gust {
+    fn pos(&self) -> Vec3 { self.pos }
+    fn prev_pos(&self) -> Vec3 { self.prev_pos }
+    fn vel(&self) -> Vec3 { self.vel }
+    fn dim(&self) -> Vec3 { DIM }
+    // Air, which never collides anyway.
+    fn mass(&self) -> f32 { 0.001 }
+    fn restitution(&self) -> f32 { 0.0 }
+
+    fn set_pos(&mut self, val: Vec3) { self.pos = val }
+    fn set_vel(&mut self, val: Vec3) { self.vel = val }
+}
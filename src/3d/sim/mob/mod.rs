@@ -0,0 +1,165 @@
+use super::terrain::{Terrain, STEP_HEIGHT};
+use super::collision;
+
+use macroquad::prelude::*;
+
+pub mod player;
+pub mod frog;
+pub mod ball;
+pub mod gust;
+pub mod flock;
+
+/// In meters per second squared, which allows realistic jumping.
+pub const GRAVITY: f32 = 30.0;
+/// Exponential decay rate of velocity on the ground, per second, which allows
+/// realistic braking.
+pub const FRICTION: f32 = 9.25;
+
+/// for mobile entities
+pub trait Mob {
+    fn pos(&self) -> Vec3;
+    /// position at the previous tick
+    fn prev_pos(&self) -> Vec3;
+    fn vel(&self) -> Vec3;
+    /// coords: forward, up, right
+    fn dim(&self) -> Vec3;
+    /// in kilograms, always positive
+    fn mass(&self) -> f32;
+    /// fraction of the closing speed kept after a collision, from 0 to 1
+    fn restitution(&self) -> f32;
+
+    /// exponential decay rate of velocity on the ground, per second
+    fn friction(&self) -> f32 { FRICTION }
+
+    fn set_pos(&mut self, val: Vec3);
+    fn set_vel(&mut self, val: Vec3);
+
+    /// position between the previous and current tick
+    ///
+    /// `alpha` is between 0 and 1
+    fn lerp_pos(&self, alpha: f32) -> Vec3 {
+        self.prev_pos().lerp(self.pos(), alpha)
+    }
+
+    fn centre(&self) -> Vec3 {
+        self.pos() + self.dim() / 2.0
+    }
+
+    fn is_on_ground(&self, terrain: &Terrain) -> bool {
+        terrain.supports(self.pos(), self.dim())
+    }
+
+    ///// surface area in metres
+    //fn area(&self) -> f32 {
+    //    let dim = self.dim();
+
+    //    2.0 * dim.x * dim.y +
+    //    2.0 * dim.y * dim.z +
+    //    2.0 * dim.z * dim.x
+    //}
+
+    /// whether the mobs intersected at the previous tick
+    fn intersected<M: Mob>(&self, other: &M) -> bool {
+        boxes_intersect(
+            self.prev_pos(), self.dim(),
+            other.prev_pos(), other.dim()
+        )
+    }
+
+    /// whether the mobs overlapped at the previous tick, rather than just
+    /// touching
+    fn overlapped<M: Mob>(&self, other: &M) -> bool {
+        collision::box_contact(
+            self.prev_pos(), self.dim(),
+            other.prev_pos(), other.dim()
+        ).is_some()
+    }
+
+    /// `time_delta` should be constant for reproducible results
+    fn apply_forces(&mut self, terrain: &Terrain, time_delta: f32) {
+        // Exponential decay rate, per second.
+        const R: f32 = 0.5;     // Allows realistic terminal velocity.
+        // Upward part of the normal of the flattest ground mobs slide down.
+        const S: f32 = 0.95;    // About 18 degrees.
+
+        let mut vel = self.vel();
+        let is_on_ground = self.is_on_ground(terrain);
+
+        // Gravity, or only the part of it along steep ground.
+        if !is_on_ground {
+            vel.y -= GRAVITY * time_delta;
+        } else {
+            let normal = terrain.ground_normal(self.pos(), self.dim());
+
+            if normal.y < S {
+                // Downhill, following the ground down.
+                let slide = (normal * normal.y - Vec3::Y) * GRAVITY;
+
+                vel.x += slide.x * time_delta;
+                vel.z += slide.z * time_delta;
+            }
+        }
+
+        // Air resistance.
+        vel *= (-R * time_delta).exp();
+
+        // Friction.
+        if is_on_ground {
+            vel *= (-self.friction() * time_delta).exp();
+        }
+
+        self.set_vel(vel);
+    }
+
+    fn apply_vel(&mut self, terrain: &Terrain, time_delta: f32) {
+        let (mut vel, dim) = (self.vel(), self.dim());
+        let was_on_ground = self.is_on_ground(terrain);
+        let mut pos = terrain.push_out(self.pos(), dim);
+
+        // One axis at a time, so that mobs slide along walls.
+        for axis in [0, 2] {
+            let start = pos[axis];
+
+            pos[axis] += vel[axis] * time_delta;
+
+            if let Some(stop) = terrain.sweep(pos, dim, axis, start) {
+                pos[axis] = stop;
+                vel[axis] *= -self.restitution();
+            }
+        }
+
+        // Look surfaces up from before falling, so none are fallen through.
+        let start = pos;
+
+        pos.y += vel.y * time_delta;
+
+        if let Some(ceiling) = terrain.ceiling_height(start, dim) {
+            if pos.y + dim.y > ceiling {
+                pos.y = ceiling - dim.y;
+                vel.y = vel.y.min(0.0);
+            }
+        }
+
+        // Prevent falling through the ground, and follow it down slopes.
+        if let Some(ground) = terrain.ground_height(start, dim) {
+            let is_following = was_on_ground && vel.y <= 0.0
+                && pos.y - ground <= STEP_HEIGHT;
+
+            if pos.y < ground || is_following { pos.y = ground }
+        }
+
+        self.set_pos(pos);
+        self.set_vel(vel);
+    }
+}
+
+/// for boxes given by their minimum corner and dimensions
+pub fn boxes_intersect(min_a: Vec3, dim_a: Vec3, min_b: Vec3, dim_b: Vec3)
+    -> bool
+{
+    let (max_a, max_b) = (dim_a + min_a, dim_b + min_b);
+
+    max_a.x >= min_b.x && min_a.x <= max_b.x &&
+    max_a.y >= min_b.y && min_a.y <= max_b.y &&
+    max_a.z >= min_b.z && min_a.z <= max_b.z
+}
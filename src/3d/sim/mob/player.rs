@@ -0,0 +1,352 @@
+use Direction::*;
+use ZoomKind::*;
+
+use super::Mob;
+use super::ball::{self, Ball};
+use super::gust::Gust;
+
+use crate::sim::terrain::Terrain;
+use crate::sim::save::PlayerSnapshot;
+
+use macroquad::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+// Average dimensions in meters.
+const HEIGHT: f32 = 1.69;
+const WIDTH: f32 = 0.4;
+// Camera is laterally in the middle.
+const CAM_OFFSET: Vec3 = vec3(WIDTH / 2.0, HEIGHT, WIDTH / 2.0);
+
+// In radians per pixel of mouse movement.
+const LOOK_SPEED: f32 = 0.000_33;
+const ZOOM_SPEED: f32 = 2.0;
+
+// In meters per second, ignoring physical forces.
+const WALK_SPEED: f32 = 35.0;
+const STRAFE_SPEED: f32 = WALK_SPEED / 2.0;
+const JUMP_SPEED: f32 = 10.0;
+
+const SPRINT_COEFF: f32 = 2.0;
+
+// Seconds of charging for the strongest throw.
+const CHARGE_TIME: f32 = 1.0;
+// Of the weakest and strongest throws, in meters per second.
+const MIN_THROW_SPEED: f32 = 20.0;
+const MAX_THROW_SPEED: f32 = 100.0;
+// Upwards, as a fraction of the throw speed.
+const THROW_LIFT: f32 = 0.15;
+// Of held frogs when launched, in meters per second.
+const MIN_LAUNCH_SPEED: f32 = 5.0;
+const MAX_LAUNCH_SPEED: f32 = 25.0;
+// From the eyes to where held frogs are carried, in meters.
+const CARRY_DIST: f32 = 2.5;
+
+// In kilograms.
+const MASS: f32 = 70.0;
+const RESTITUTION: f32 = 0.2;
+
+pub struct Player {
+    cam: Camera3D,
+    prev_pos: Vec3,
+    /// Rotation.
+    rot: Vec3,
+    vel: Vec3,
+
+    front: Vec3,
+    right: Vec3,
+    /// Of the next throw, from 0 to 1.
+    charge: f32,
+    projectile: Projectile,
+
+    pub is_sprinting: bool,
+    pub is_victorious: bool
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Front,
+    Back,
+    Right,
+    Left
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ZoomKind {
+    In,
+    Out
+}
+
+/// what the player throws
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Projectile {
+    #[default]
+    Ball,
+    Sticky,
+    Bouncy,
+    Heavy,
+    Gust
+}
+
+/// a thrown projectile, ready to be added to the world
+pub enum Thrown {
+    Ball(Ball),
+    Gust(Gust)
+}
+
+impl Player {
+    /// position of the camera
+    pub fn eye(&self) -> Vec3 { self.cam.position }
+    pub fn front(&self) -> Vec3 { self.front }
+    /// of the next throw, from 0 to 1
+    pub fn charge(&self) -> f32 { self.charge }
+    pub fn projectile(&self) -> Projectile { self.projectile }
+
+    /// the player can move if they either
+    /// - are in contact with the ground
+    /// - are victorious and above the void
+    pub fn can_move(&self, terrain: &Terrain) -> bool {
+        self.is_victorious && !self.is_in_void(terrain)
+        || self.is_on_ground(terrain)
+    }
+
+    /// camera positioned between the previous and current tick
+    pub fn cam(&self, alpha: f32) -> Camera3D {
+        let position = self.lerp_pos(alpha) + CAM_OFFSET;
+
+        Camera3D {
+            position,
+            target: position + self.front,
+            ..self.cam
+        }
+    }
+
+    pub fn look(&mut self, mouse_delta: Vec2) {
+        const NORTH: Vec3 = vec3(0.0, 1.0, 0.0);
+        const MAX_PITCH: f32 = 1.5;     // Straight up.
+
+        let (pitch, yaw) = (&mut self.rot.z, &mut self.rot.y);
+
+        // The mouse delta already covers the elapsed time.
+        *pitch = (*pitch - mouse_delta.y * LOOK_SPEED)
+            .clamp(-MAX_PITCH, MAX_PITCH);      // Don't break your neck!
+
+        *yaw += mouse_delta.x * LOOK_SPEED;
+
+        let pitch_cos = pitch.cos();
+
+        self.front = vec3(
+            yaw.cos() * pitch_cos,
+            pitch.sin(),
+            yaw.sin() * pitch_cos
+        ).normalize();
+
+        self.right = self.front.cross(NORTH).normalize();
+        self.cam.up = self.right.cross(self.front).normalize();
+        self.cam.target = self.cam.position + self.front;
+    }
+
+    pub fn zoom(&mut self, kind: ZoomKind, time_delta: f32) {
+        // The default FOV is 45.3 degrees.
+        const FOV_MIN: f32 = 44.27;
+        const FOV_MAX: f32 = 46.03;
+
+        self.cam.fovy = match kind {
+            In => FOV_MIN.max(self.cam.fovy - ZOOM_SPEED * time_delta),
+            Out => FOV_MAX.min(self.cam.fovy + ZOOM_SPEED * time_delta)
+        }
+    }
+
+    pub fn accel(&mut self, dir: Direction, time_delta: f32) {
+        // Directions relative to the horizontal plane (the ground).
+        let front_h = vec3(self.front.x, 0.0, self.front.z).normalize();
+        let right_h = vec3(self.right.x, 0.0, self.right.z).normalize();
+
+        let coeff = if self.is_sprinting {
+            SPRINT_COEFF
+        } else {
+            1.0
+        } * time_delta;
+
+        match dir {
+            Front => self.vel += front_h * coeff * WALK_SPEED,
+            Back  => self.vel -= front_h * coeff * STRAFE_SPEED,
+            Right => self.vel += right_h * coeff * STRAFE_SPEED,
+            Left  => self.vel -= right_h * coeff * STRAFE_SPEED
+        }
+    }
+
+    /// for testing
+    /// respects sprint
+    pub fn super_leap(&mut self, time_delta: f32) {
+        for _ in 0..100 { self.accel(Front, time_delta) }
+        for _ in 0..3   { self.jump() }
+    }
+
+    pub fn jump(&mut self) {
+        self.vel.y += JUMP_SPEED;
+    }
+
+    /// this is the only function that actually moves the player
+    pub fn update(&mut self, terrain: &Terrain, time_delta: f32) {
+        self.prev_pos = self.pos();
+        self.apply_forces(terrain, time_delta);
+        self.apply_vel(terrain, time_delta);
+    }
+
+    /// the next throw gets stronger, until fully charged
+    pub fn charge_throw(&mut self, time_delta: f32) {
+        self.charge = (self.charge + time_delta / CHARGE_TIME).min(1.0);
+    }
+
+    /// the next throw starts from no charge again
+    pub fn drop_charge(&mut self) {
+        self.charge = 0.0;
+    }
+
+    /// what is thrown next
+    pub fn select(&mut self, projectile: Projectile) {
+        self.projectile = projectile;
+    }
+
+    /// what would be thrown with the current charge
+    pub fn aim(&self) -> Thrown {
+        let Some(kind) = self.projectile.ball_kind() else {
+            return Thrown::Gust(
+                Gust::new(self.cam.position, self.front, self.charge)
+            )
+        };
+
+        let speed = MIN_THROW_SPEED
+            + (MAX_THROW_SPEED - MIN_THROW_SPEED) * self.charge;
+        let vel = (self.front + Vec3::Y * THROW_LIFT) * speed + self.vel;
+        let pos = self.cam.position - 0.2;
+
+        Thrown::Ball(Ball::new(pos, vel, kind))
+    }
+
+    /// where the centre of a held frog is carried
+    pub fn carry_point(&self) -> Vec3 {
+        self.cam.position + self.front * CARRY_DIST
+    }
+
+    /// velocity to launch a held frog with, using up the charge
+    pub fn launch(&mut self) -> Vec3 {
+        let speed = MIN_LAUNCH_SPEED
+            + (MAX_LAUNCH_SPEED - MIN_LAUNCH_SPEED) * self.charge;
+
+        self.charge = 0.0;
+        self.front * speed + self.vel
+    }
+
+    /// uses up the charge
+    pub fn throw(&mut self) -> Thrown {
+        let thrown = self.aim();
+
+        self.charge = 0.0;
+        thrown
+    }
+}
+
+impl Player {
+    /// `pos` is at the feet of the player
+    pub fn new(pos: Vec3) -> Self {
+        let position = pos + CAM_OFFSET;
+
+        let cam = Camera3D {
+            position,
+            up: Vec3::Y,
+            target: position + Vec3::X,     // Position + front.
+            fovy: 45.3,
+            ..Default::default()
+        };
+
+        Self {
+            cam, prev_pos: pos, rot: Vec3::ZERO, vel: Vec3::ZERO,
+            front: Vec3::X, right: Vec3::Z,
+            charge: 0.0, projectile: Projectile::default(),
+            is_sprinting: false, is_victorious: false
+        }
+    }
+
+    pub fn snapshot(&self) -> PlayerSnapshot {
+        PlayerSnapshot {
+            cam_position: self.cam.position.into(),
+            cam_target: self.cam.target.into(),
+            cam_up: self.cam.up.into(),
+            fovy: self.cam.fovy,
+
+            prev_pos: self.prev_pos.into(),
+            rot: self.rot.into(),
+            vel: self.vel.into(),
+            front: self.front.into(),
+            right: self.right.into(),
+            charge: self.charge,
+            projectile: self.projectile,
+
+            is_sprinting: self.is_sprinting,
+            is_victorious: self.is_victorious
+        }
+    }
+
+    pub fn restore(snap: &PlayerSnapshot) -> Self {
+        let cam = Camera3D {
+            position: snap.cam_position.into(),
+            target: snap.cam_target.into(),
+            up: snap.cam_up.into(),
+            fovy: snap.fovy,
+            ..Default::default()
+        };
+
+        Self {
+            cam,
+            prev_pos: snap.prev_pos.into(),
+            rot: snap.rot.into(),
+            vel: snap.vel.into(),
+            front: snap.front.into(),
+            right: snap.right.into(),
+            charge: snap.charge,
+            projectile: snap.projectile,
+
+            is_sprinting: snap.is_sprinting,
+            is_victorious: snap.is_victorious
+        }
+    }
+
+    pub fn is_in_void(&self, terrain: &Terrain) -> bool {
+        terrain.is_in_void(self.pos())
+    }
+}
+
+impl Mob for Player {
+    fn pos(&self) -> Vec3 { self.cam.position - CAM_OFFSET }
+    fn prev_pos(&self) -> Vec3 { self.prev_pos }
+    fn vel(&self) -> Vec3 { self.vel }
+    fn dim(&self) -> Vec3 { vec3(WIDTH, HEIGHT, WIDTH) }
+    fn mass(&self) -> f32 { MASS }
+    fn restitution(&self) -> f32 { RESTITUTION }
+
+    fn set_pos(&mut self, val: Vec3) {
+        self.cam.position = val + CAM_OFFSET;
+        self.cam.target = self.cam.position + self.front;
+    }
+
+    fn set_vel(&mut self, val: Vec3) { self.vel = val }
+}
+
+impl Projectile {
+    /// none for gusts
+    pub fn ball_kind(self) -> Option<ball::Kind> {
+        match self {
+            Self::Ball => Some(ball::Kind::Regular),
+            Self::Sticky => Some(ball::Kind::Sticky),
+            Self::Bouncy => Some(ball::Kind::Bouncy),
+            Self::Heavy => Some(ball::Kind::Heavy),
+            Self::Gust => None
+        }
+    }
+}
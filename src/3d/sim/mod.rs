@@ -0,0 +1,30 @@
+//! game state and rules, independent of windowing, drawing and audio
+//!
+//! the front end feeds an [`Input`] to [`World::step`] and reacts to the
+//! returned [`Event`]s
+
+pub mod world;
+pub mod terrain;
+pub mod heightmap;
+pub mod island;
+pub mod mob;
+pub mod input;
+pub mod level;
+pub mod rules;
+pub mod zone;
+pub mod solid;
+pub mod collision;
+pub mod ray;
+pub mod trajectory;
+pub mod broadphase;
+pub mod save;
+pub mod demo;
+pub mod rng;
+
+pub use world::{World, Event};
+pub use input::Input;
+pub use level::Level;
+pub use rng::Rng;
+
+/// Recommended simulation time step, in seconds.
+pub const TICK: f32 = 1.0 / 60.0;
@@ -0,0 +1,47 @@
+use macroquad::prelude::*;
+
+/// what a ray hit
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Target {
+    Player,
+    /// Index of the frog.
+    Frog(usize),
+    /// Index of the ball, from the oldest.
+    Ball(usize),
+    /// Flat or from the heightmap.
+    Ground,
+    /// Index of the solid of the level.
+    Solid(usize)
+}
+
+/// where a ray first hits something
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RayHit {
+    pub target: Target,
+    /// From the origin of the ray.
+    pub dist: f32,
+    pub pos: Vec3,
+    /// Unit vector perpendicular to the surface hit, facing the ray.
+    pub normal: Vec3
+}
+
+impl RayHit {
+    /// the ray from `origin` along the unit vector `dir` hit `target` after
+    /// `dist`
+    pub fn new(
+        target: Target,
+        origin: Vec3,
+        dir: Vec3,
+        dist: f32,
+        normal: Vec3
+    ) -> Self {
+        Self { target, dist, pos: origin + dir * dist, normal }
+    }
+}
+
+/// whichever of `hits` is nearest
+pub fn nearest<I>(hits: I) -> Option<RayHit>
+    where I: IntoIterator<Item = RayHit>
+{
+    hits.into_iter().min_by(|a, b| a.dist.total_cmp(&b.dist))
+}
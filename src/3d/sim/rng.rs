@@ -0,0 +1,46 @@
+/// a small deterministic random number generator (SplitMix64)
+///
+/// owned by the world, so the same seed and input always give the same run
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// to save and restore the generator mid-sequence
+    pub fn state(&self) -> u64 { self.state }
+
+    pub fn from_state(state: u64) -> Self {
+        Self { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+        z ^ (z >> 31)
+    }
+
+    /// between 0 (inclusive) and 1 (exclusive)
+    pub fn next_f32(&mut self) -> f32 {
+        // The top 24 bits fit exactly in the mantissa.
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// chance is between 0 and 1
+    pub fn chance(&mut self, chance: f32) -> bool {
+        self.next_f32() < chance
+    }
+
+    /// `n` or `-n`, evenly
+    pub fn sign(&mut self, n: f32) -> f32 {
+        if self.chance(0.5) { n } else { -n }
+    }
+}
@@ -0,0 +1,262 @@
+use super::World;
+use super::mob::{Mob, boxes_intersect};
+
+use macroquad::prelude::*;
+
+use serde::Deserialize;
+
+use std::fmt;
+
+/// Gap still counted as touching, as colliding mobs are pushed apart.
+const TOUCH_MARGIN: f32 = 0.05;
+
+/// a goal, evaluated against the world every tick
+///
+/// frogs are numbered from 1, in the order they are listed in the level
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum Rule {
+    All(Vec<Rule>),
+    Any(Vec<Rule>),
+    Not(Box<Rule>),
+    Touches(MobRef, MobRef),
+    /// The centre of the mob is within the box.
+    InZone { mob: MobRef, min: [f32; 3], max: [f32; 3] },
+    OutsideBounds(MobRef),
+    /// In seconds of simulated time.
+    TimeElapsed(f32),
+    /// Balls striking a frog, or any frog if none is given.
+    Hits { frog: Option<MobRef>, count: u32 }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "MobRefDef")]
+pub enum MobRef {
+    Player,
+    /// Index into the frogs of the world.
+    Frog(usize)
+}
+
+/// the outcome of a rule, and of every rule it is made of
+#[derive(Default)]
+pub struct Status {
+    pub label: String,
+    pub is_met: bool,
+    /// Between 0 and 1.
+    pub progress: f32,
+    pub children: Vec<Status>
+}
+
+impl Rule {
+    pub fn eval(&self, world: &World) -> Status {
+        match self {
+            Self::All(rules) => {
+                let children = eval_all(rules, world);
+                let is_met = children.iter().all(|s| s.is_met);
+                let progress = children.iter()
+                    .map(|s| s.progress)
+                    .sum::<f32>() / children.len() as f32;
+
+                Status::group("all of", is_met, progress, children)
+            }
+            Self::Any(rules) => {
+                let children = eval_all(rules, world);
+                let is_met = children.iter().any(|s| s.is_met);
+                let progress = children.iter()
+                    .map(|s| s.progress)
+                    .fold(0.0, f32::max);
+
+                Status::group("any of", is_met, progress, children)
+            }
+            Self::Not(rule) => {
+                let child = rule.eval(world);
+                let is_met = !child.is_met;
+                let progress = is_met as u8 as f32;
+
+                Status::group("none of", is_met, progress, vec![child])
+            }
+            Self::Touches(a, b) => {
+                let ((pos_a, dim_a), (pos_b, dim_b)) =
+                    (a.bounds(world), b.bounds(world));
+                let margin = Vec3::splat(TOUCH_MARGIN);
+
+                Status::leaf(
+                    format!("{a} touches {b}"),
+                    boxes_intersect(
+                        pos_a - margin, dim_a + margin * 2.0,
+                        pos_b, dim_b
+                    )
+                )
+            }
+            Self::InZone { mob, min, max } => {
+                let (pos, dim) = mob.bounds(world);
+                let centre = pos + dim / 2.0;
+                let (min, max) = (Vec3::from(*min), Vec3::from(*max));
+
+                Status::leaf(
+                    format!("{mob} in zone"),
+                    centre.cmpge(min).all() && centre.cmple(max).all()
+                )
+            }
+            Self::OutsideBounds(mob) => {
+                let (pos, _) = mob.bounds(world);
+
+                Status::leaf(
+                    format!("{mob} outside the terrain"),
+                    world.terrain.is_outside_bounds(pos)
+                )
+            }
+            Self::TimeElapsed(secs) => {
+                let time = world.time().min(*secs);
+
+                Status::counter(
+                    format!("{time:.0}/{secs:.0} s elapsed"),
+                    time, *secs
+                )
+            }
+            Self::Hits { frog, count } => {
+                let hits = match frog {
+                    Some(MobRef::Frog(i)) => world.hits(*i),
+                    _ => (0..world.frogs.len()).map(|i| world.hits(i)).sum()
+                };
+                let target = match frog {
+                    Some(f) => f.to_string(),
+                    None => "frogs".to_string()
+                };
+
+                Status::counter(
+                    format!("{}/{count} hits on {target}", hits.min(*count)),
+                    hits as f32, *count as f32
+                )
+            }
+        }
+    }
+
+    /// ensures the rule can be evaluated in a world with `frog_count` frogs
+    pub fn validate(&self, frog_count: usize) -> Result<(), String> {
+        let check_mob = |mob: &MobRef| match mob {
+            MobRef::Frog(i) if *i >= frog_count => {
+                Err(format!("{mob} does not exist"))
+            }
+            _ => Ok(())
+        };
+
+        match self {
+            Self::All(rules) | Self::Any(rules) => {
+                if rules.is_empty() {
+                    return Err("all/any must contain at least one rule".into())
+                }
+
+                rules.iter().try_for_each(|r| r.validate(frog_count))
+            }
+            Self::Not(rule) => rule.validate(frog_count),
+            Self::Touches(a, b) => check_mob(a).and(check_mob(b)),
+            Self::InZone { mob, min, max } => {
+                let (min, max) = (Vec3::from(*min), Vec3::from(*max));
+
+                let is_valid = min.is_finite() && max.is_finite()
+                    && min.cmple(max).all();
+
+                if !is_valid {
+                    return Err(format!("zone of {mob} must have min <= max"))
+                }
+
+                check_mob(mob)
+            }
+            Self::OutsideBounds(mob) => check_mob(mob),
+            Self::TimeElapsed(secs) => {
+                if secs.is_finite() && *secs >= 0.0 {
+                    Ok(())
+                } else {
+                    Err("time_elapsed must not be negative".into())
+                }
+            }
+            Self::Hits { frog, count } => {
+                if *count == 0 {
+                    return Err("hits count must be positive".into())
+                }
+
+                match frog {
+                    Some(MobRef::Player) => {
+                        Err("hits can only be counted on frogs".into())
+                    }
+                    Some(f) => check_mob(f),
+                    None => Ok(())
+                }
+            }
+        }
+    }
+}
+
+impl MobRef {
+    /// minimum corner and dimensions
+    fn bounds(self, world: &World) -> (Vec3, Vec3) {
+        match self {
+            Self::Player => (world.player.pos(), world.player.dim()),
+            Self::Frog(i) => (world.frogs[i].pos(), world.frogs[i].dim())
+        }
+    }
+}
+
+impl fmt::Display for MobRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Player => write!(f, "player"),
+            Self::Frog(i) => write!(f, "frog {}", i + 1)
+        }
+    }
+}
+
+impl Status {
+    fn leaf(label: String, is_met: bool) -> Self {
+        let progress = is_met as u8 as f32;
+
+        Self { label, is_met, progress, children: vec![] }
+    }
+
+    fn counter(label: String, val: f32, target: f32) -> Self {
+        let progress = if target > 0.0 {
+            (val / target).min(1.0)
+        } else {
+            1.0
+        };
+
+        Self { label, is_met: val >= target, progress, children: vec![] }
+    }
+
+    fn group(
+        label: &str,
+        is_met: bool,
+        progress: f32,
+        children: Vec<Status>
+    ) -> Self {
+        Self { label: label.to_string(), is_met, progress, children }
+    }
+}
+
+/// either "player" or a frog number
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MobRefDef {
+    Frog(usize),
+    Name(String)
+}
+
+impl TryFrom<MobRefDef> for MobRef {
+    type Error = String;
+
+    fn try_from(def: MobRefDef) -> Result<Self, Self::Error> {
+        match def {
+            MobRefDef::Frog(0) => Err("frogs are numbered from 1".into()),
+            MobRefDef::Frog(n) => Ok(Self::Frog(n - 1)),
+            MobRefDef::Name(name) if name == "player" => Ok(Self::Player),
+            MobRefDef::Name(name) => Err(format!(
+                "expected \"player\" or a frog number, found \"{name}\""
+            ))
+        }
+    }
+}
+
+fn eval_all(rules: &[Rule], world: &World) -> Vec<Status> {
+    rules.iter().map(|r| r.eval(world)).collect()
+}
@@ -0,0 +1,202 @@
+use super::mob::{frog, ball};
+use super::mob::player::Projectile;
+
+use macroquad::prelude::*;
+
+use serde::{Serialize, Deserialize};
+
+use std::{fmt, fs, io};
+use std::path::Path;
+
+/// Bumped whenever the format changes, as older saves cannot be read.
+pub const VERSION: u32 = 10;
+
+/// the full state of a world, except what comes from its level
+///
+/// vectors are stored as arrays, which survive a round trip exactly, and
+/// 64-bit integers as hexadecimal strings, as TOML integers are signed
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+
+    pub seed: String,
+    /// State of the random number generator.
+    pub rng: String,
+
+    pub time: f32,
+    pub hits: Vec<u32>,
+    pub checkpoint: [f32; 3],
+    pub lives: u32,
+    /// Unlimited if none.
+    pub ammo: Option<u32>,
+    /// Index of the frog the player carries.
+    pub carried: Option<usize>,
+    pub is_player_dead: bool,
+
+    pub terrain: TerrainSnapshot,
+    pub player: PlayerSnapshot,
+    pub frogs: Vec<FrogSnapshot>,
+    /// From oldest to newest.
+    pub balls: Vec<BallSnapshot>,
+    pub gusts: Vec<GustSnapshot>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct TerrainSnapshot {
+    pub wall_color: [f32; 4],
+    pub grid_colors: ([f32; 4], [f32; 4]),
+    pub wall_pos: [f32; 3],
+    pub wall_dim: [f32; 3]
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub cam_position: [f32; 3],
+    pub cam_target: [f32; 3],
+    pub cam_up: [f32; 3],
+    pub fovy: f32,
+
+    pub prev_pos: [f32; 3],
+    pub rot: [f32; 3],
+    pub vel: [f32; 3],
+    pub front: [f32; 3],
+    pub right: [f32; 3],
+    pub charge: f32,
+    pub projectile: Projectile,
+
+    pub is_sprinting: bool,
+    pub is_victorious: bool
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FrogSnapshot {
+    pub pos: [f32; 3],
+    pub prev_pos: [f32; 3],
+    pub vel: [f32; 3],
+    pub color: [f32; 4],
+    pub mass: f32,
+    pub restitution: f32,
+
+    pub is_calm: bool,
+    pub state: frog::State,
+    pub timer: f32,
+    pub scatter_timer: f32
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BallSnapshot {
+    pub pos: [f32; 3],
+    pub prev_pos: [f32; 3],
+    pub vel: [f32; 3],
+    pub mass: f32,
+    pub restitution: f32,
+    pub kind: ball::Kind,
+    pub age: f32,
+    /// Index of the frog it is glued to.
+    pub stuck_to: Option<usize>,
+    pub stuck_offset: [f32; 3]
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GustSnapshot {
+    pub origin: [f32; 3],
+    pub pos: [f32; 3],
+    pub prev_pos: [f32; 3],
+    pub vel: [f32; 3],
+    pub strength: f32,
+    pub age: f32
+}
+
+/// for saves and demos
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Write(toml::ser::Error),
+    Read(toml::de::Error),
+    Version { found: u32, version: u32 },
+    Invalid(String),
+    /// The file does not fit the current level.
+    Mismatch(String)
+}
+
+impl Snapshot {
+    pub fn write(&self, path: &Path) -> Result<(), SaveError> {
+        write_toml(self, path)
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SaveError> {
+        read_versioned(path, VERSION)
+    }
+
+    /// stable across builds and platforms, unlike `std::hash`
+    pub fn hash(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let text = toml::to_string(self)
+            .expect("snapshot should always serialize");
+
+        text.bytes().fold(FNV_OFFSET, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(FNV_PRIME)
+        })
+    }
+}
+
+pub fn to_hex(n: u64) -> String {
+    format!("{n:x}")
+}
+
+/// `name` describes the value in the error
+pub fn parse_hex(text: &str, name: &str) -> Result<u64, SaveError> {
+    u64::from_str_radix(text, 16).map_err(|_| {
+        SaveError::Invalid(format!("{name} is not hexadecimal: \"{text}\""))
+    })
+}
+
+/// creates missing directories
+pub fn write_toml<T: Serialize>(val: &T, path: &Path) -> Result<(), SaveError> {
+    let text = toml::to_string(val).map_err(SaveError::Write)?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(SaveError::Io)?;
+    }
+
+    fs::write(path, text).map_err(SaveError::Io)
+}
+
+/// fails if the `version` field of the file is not `version`
+pub fn read_versioned<T>(path: &Path, version: u32) -> Result<T, SaveError>
+    where T: for<'de> Deserialize<'de>
+{
+    let text = fs::read_to_string(path).map_err(SaveError::Io)?;
+
+    // Check the version first, in case the rest no longer parses.
+    #[derive(Deserialize)]
+    struct Header { version: u32 }
+
+    let header: Header = toml::from_str(&text).map_err(SaveError::Read)?;
+
+    if header.version != version {
+        return Err(SaveError::Version { found: header.version, version })
+    }
+
+    toml::from_str(&text).map_err(SaveError::Read)
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "cannot access file: {e}"),
+            Self::Write(e) => write!(f, "cannot write file: {e}"),
+            Self::Read(e) => write!(f, "malformed file: {e}"),
+            Self::Version { found, version } => write!(
+                f, "file is from version {found}, but only {version} is \
+                    supported"
+            ),
+            Self::Invalid(msg) => write!(f, "invalid file: {msg}"),
+            Self::Mismatch(msg) => write!(f, "file does not fit level: {msg}")
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
@@ -0,0 +1,127 @@
+use Rise::*;
+
+use super::collision;
+
+use macroquad::prelude::*;
+
+use serde::Deserialize;
+
+/// static level geometry that mobs stand on and bump into
+///
+/// either a whole box, or a ramp filling the part of the box below a slope
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "SolidDef")]
+pub struct Solid {
+    pub min: Vec3,
+    pub max: Vec3,
+    /// For ramps, where the top rises from `min.y` to `max.y`.
+    pub ramp: Option<Rise>
+}
+
+/// a horizontal direction, written as "+x", "-x", "+z" or "-z"
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Rise {
+    #[serde(rename = "+x")]
+    PosX,
+    #[serde(rename = "-x")]
+    NegX,
+    #[serde(rename = "+z")]
+    PosZ,
+    #[serde(rename = "-z")]
+    NegZ
+}
+
+impl Solid {
+    pub fn dim(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    /// highest point of the top above the horizontal rectangle between the
+    /// corners `min` and `max`, which should overlap the solid
+    pub fn top_over(&self, min: Vec3, max: Vec3) -> f32 {
+        let Some(rise) = self.ramp else { return self.max.y };
+
+        let dim = self.dim();
+        // How far up the slope, from 0 to 1.
+        let progress = match rise {
+            PosX => (max.x - self.min.x) / dim.x,
+            NegX => (self.max.x - min.x) / dim.x,
+            PosZ => (max.z - self.min.z) / dim.z,
+            NegZ => (self.max.z - min.z) / dim.z
+        };
+
+        self.min.y + progress.clamp(0.0, 1.0) * dim.y
+    }
+
+    /// upward unit vector perpendicular to the top
+    pub fn normal(&self) -> Vec3 {
+        let dim = self.dim();
+
+        match self.ramp {
+            None => Vec3::Y,
+            Some(PosX) => vec3(-dim.y, dim.x, 0.0).normalize(),
+            Some(NegX) => vec3(dim.y, dim.x, 0.0).normalize(),
+            Some(PosZ) => vec3(0.0, dim.z, -dim.y).normalize(),
+            Some(NegZ) => vec3(0.0, dim.z, dim.y).normalize()
+        }
+    }
+
+    /// distance from `origin` along the unit vector `dir` to where the ray
+    /// enters the solid, and the normal of the surface there
+    pub fn ray_hit(&self, origin: Vec3, dir: Vec3) -> Option<(f32, Vec3)> {
+        let (mut enter, mut exit, mut normal) =
+            collision::ray_span(origin, dir, self.min, self.max)?;
+
+        if self.ramp.is_some() {
+            // Ramps are the part of the box below the slope, which goes
+            // through its centre.
+            let slope = self.normal();
+            let height = (origin - (self.min + self.max) / 2.0).dot(slope);
+            let rate = dir.dot(slope);
+
+            if rate == 0.0 {
+                if height > 0.0 { return None }
+            } else if rate < 0.0 {
+                let t = -height / rate;
+
+                if t > enter {
+                    enter = t;
+                    normal = slope;
+                }
+            } else {
+                exit = exit.min(-height / rate);
+            }
+        }
+
+        (enter <= exit).then_some((enter, normal))
+    }
+
+    /// whether the horizontal rectangle between the corners `min` and `max`
+    /// overlaps the solid seen from above, rather than just touching it
+    pub fn overlaps_xz(&self, min: Vec3, max: Vec3) -> bool {
+        min.x < self.max.x && max.x > self.min.x &&
+        min.z < self.max.z && max.z > self.min.z
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SolidDef {
+    min: [f32; 3],
+    max: [f32; 3],
+    ramp: Option<Rise>
+}
+
+impl TryFrom<SolidDef> for Solid {
+    type Error = &'static str;
+
+    fn try_from(def: SolidDef) -> Result<Self, Self::Error> {
+        let (min, max) = (Vec3::from(def.min), Vec3::from(def.max));
+
+        if min.is_finite() && max.is_finite() && min.cmplt(max).all() {
+            Ok(Self { min, max, ramp: def.ramp })
+        } else {
+            Err("solid must have min < max on every axis")
+        }
+    }
+}
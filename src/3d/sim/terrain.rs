@@ -0,0 +1,442 @@
+use macroquad::prelude::*;
+
+use super::{Level, Rng};
+use super::heightmap::Heightmap;
+use super::island::{Island, IslandSettings};
+use super::mob::{Mob, player::Player};
+use super::save::TerrainSnapshot;
+use super::ray::{self, RayHit, Target};
+use super::solid::Solid;
+
+/// Balls falling below this height, in meters, are gone for good.
+pub const VOID_END: f32 = -1100.0;
+
+/// Highest ledge mobs walk up onto without jumping.
+pub const STEP_HEIGHT: f32 = 0.3;
+/// Steepest ground mobs walk up, as height gained per distance travelled.
+const MAX_CLIMB: f32 = 1.0;
+/// Mixed into the seed of a run, so its islands do not depend on anything
+/// else it generates.
+const ISLAND_STREAM: u64 = 0x15_1A_4D_5E_ED;
+/// Mobs this close above the ground stand on it, despite rounding errors.
+const GROUND_TOLERANCE: f32 = 0.001;
+
+pub struct Terrain {
+    /// Side of the square ground, centred on the origin.
+    width: f32,
+    wall: WallSettings,
+    island_settings: Option<IslandSettings>,
+    /// Points that must be on land, like where frogs start.
+    anchors: Vec<Vec3>,
+    /// Replace the square ground if any.
+    islands: Option<Vec<Island>>,
+    heightmap: Option<Heightmap>,
+    solids: Vec<Solid>,
+    /// How far below the ground the player dies.
+    void_depth: f32,
+
+    wall_color: Color,
+    grid_colors: (Color, Color),
+
+    wall_pos: Vec3,
+    wall_dim: Vec3
+}
+
+/// behaviour of the cat wall surrounding the terrain
+#[derive(Clone, Copy)]
+pub struct WallSettings {
+    pub size: f32,
+    pub shake_coeff: f32,
+    pub stretch_coeff: f32,
+    /// Furthest the wall can shake from the origin on each axis.
+    pub max_offset: f32,
+    /// Limits of each side of the wall while stretching.
+    pub dim_limit: (f32, f32)
+}
+
+impl Terrain {
+    /// islands, if the level has any, are generated from `seed`
+    pub fn new(level: &Level, seed: u64) -> Self {
+        let wall = level.wall;
+        let anchors = [level.spawn].into_iter()
+            .chain(level.frogs.iter().map(|f| f.pos))
+            .chain(level.checkpoints.iter().map(|c| c.floor()))
+            .collect();
+
+        let mut terrain = Self {
+            width: level.width,
+            wall,
+            island_settings: level.islands,
+            anchors,
+            islands: None,
+            heightmap: level.heightmap.clone(),
+            solids: level.solids.clone(),
+            void_depth: level.void_depth,
+
+            wall_color: WHITE,
+            grid_colors: (WHITE, GRAY),
+
+            wall_pos: Vec3::ZERO,
+            wall_dim: Vec3::splat(wall.size)
+        };
+
+        terrain.generate_islands(seed);
+        terrain
+    }
+
+    pub fn width(&self) -> f32 { self.width }
+    pub fn wall_color(&self) -> Color { self.wall_color }
+    pub fn grid_colors(&self) -> (Color, Color) { self.grid_colors }
+    pub fn wall_pos(&self) -> Vec3 { self.wall_pos }
+    pub fn wall_dim(&self) -> Vec3 { self.wall_dim }
+    pub fn heightmap(&self) -> Option<&Heightmap> { self.heightmap.as_ref() }
+    pub fn solids(&self) -> &[Solid] { &self.solids }
+    /// how far below the ground the player dies
+    pub fn void_depth(&self) -> f32 { self.void_depth }
+    pub fn islands(&self) -> Option<&[Island]> { self.islands.as_deref() }
+
+    pub fn snapshot(&self) -> TerrainSnapshot {
+        TerrainSnapshot {
+            wall_color: self.wall_color.into(),
+            grid_colors: (
+                self.grid_colors.0.into(),
+                self.grid_colors.1.into()
+            ),
+            wall_pos: self.wall_pos.into(),
+            wall_dim: self.wall_dim.into()
+        }
+    }
+
+    /// islands are generated again from `seed`, and the rest of the shape
+    /// of the ground and the wall settings are kept
+    pub fn restore(&mut self, snap: &TerrainSnapshot, seed: u64) {
+        self.generate_islands(seed);
+        self.wall_color = snap.wall_color.into();
+        self.grid_colors = (
+            snap.grid_colors.0.into(),
+            snap.grid_colors.1.into()
+        );
+        self.wall_pos = snap.wall_pos.into();
+        self.wall_dim = snap.wall_dim.into();
+    }
+
+    pub fn update(&mut self, player: &Player, rng: &mut Rng, time_delta: f32) {
+        self.update_grid(player);
+        self.update_wall(player, rng, time_delta);
+    }
+
+    pub fn is_outside_bounds(&self, pos: Vec3) -> bool {
+        pos.y < 0.0 || !self.is_land(pos.x, pos.z)
+    }
+
+    /// whether the player dies at `pos`, having fallen too far
+    pub fn is_in_void(&self, pos: Vec3) -> bool {
+        pos.y <= -self.void_depth
+    }
+
+    /// whether there is ground at `x` and `z`, rather than void
+    pub fn is_land(&self, x: f32, z: f32) -> bool {
+        match &self.islands {
+            Some(islands) => islands.iter().any(|i| i.contains(x, z)),
+            None => {
+                let border = self.width / 2.0;
+
+                x.abs() <= border && z.abs() <= border
+            }
+        }
+    }
+
+    /// height of the highest surface below a box at `pos` with dimensions
+    /// `dim`, if any
+    ///
+    /// surfaces up to `STEP_HEIGHT` above its feet count, so that mobs walk
+    /// up onto them
+    pub fn ground_height(&self, pos: Vec3, dim: Vec3) -> Option<f32> {
+        self.ground(pos, dim).map(|(height, _)| height)
+    }
+
+    /// upward unit vector perpendicular to the ground below a box at `pos`
+    /// with dimensions `dim`, straight up if there is none
+    pub fn ground_normal(&self, pos: Vec3, dim: Vec3) -> Vec3 {
+        self.ground(pos, dim).map_or(Vec3::Y, |(_, normal)| normal)
+    }
+
+    /// whether a box at `pos` with dimensions `dim` stands on the ground
+    pub fn supports(&self, pos: Vec3, dim: Vec3) -> bool {
+        self.ground_height(pos, dim)
+            .is_some_and(|g| (pos.y - g).abs() <= GROUND_TOLERANCE)
+    }
+
+    /// height of the lowest solid above a box at `pos` with dimensions `dim`,
+    /// if any
+    pub fn ceiling_height(&self, pos: Vec3, dim: Vec3) -> Option<f32> {
+        let (min, max) = (pos, pos + dim);
+
+        self.solids.iter()
+            .filter(|s| s.overlaps_xz(min, max))
+            .map(|s| s.min.y)
+            .filter(|&bottom| bottom + GROUND_TOLERANCE >= max.y)
+            .reduce(f32::min)
+    }
+
+    /// moves a box at `pos` with dimensions `dim` sideways out of the solids
+    /// it is stuck in, the shortest way
+    pub fn push_out(&self, mut pos: Vec3, dim: Vec3) -> Vec3 {
+        for s in &self.solids {
+            if !blocks(s, pos, pos + dim) { continue }
+
+            let centre_delta = (pos + dim / 2.0) - (s.min + s.max) / 2.0;
+            let push = |i: usize| if centre_delta[i] < 0.0 {
+                s.min[i] - dim[i] - pos[i]
+            } else {
+                s.max[i] - pos[i]
+            };
+            let (push_x, push_z) = (push(0), push(2));
+
+            if push_x.abs() <= push_z.abs() {
+                pos.x += push_x;
+            } else {
+                pos.z += push_z;
+            }
+        }
+
+        pos
+    }
+
+    /// where a box with dimensions `dim`, moved along the horizontal `axis`
+    /// from `start` to `pos`, is stopped by the first solid in its way, if
+    /// any
+    pub fn sweep(&self, pos: Vec3, dim: Vec3, axis: usize, start: f32)
+        -> Option<f32>
+    {
+        if pos[axis] == start { return None }
+
+        let is_forward = pos[axis] > start;
+
+        // Too steep to walk up, so the box goes no further.
+        if let Some(floor) = self.floor_height(pos, dim) {
+            let climb = (pos[axis] - start).abs() * MAX_CLIMB;
+
+            if floor > pos.y + STEP_HEIGHT + climb { return Some(start) }
+        }
+
+        let (mut min, mut max) = (pos, pos + dim);
+
+        // Cover every position along the way, so thin walls are not missed.
+        min[axis] = start.min(pos[axis]);
+        max[axis] = start.max(pos[axis]) + dim[axis];
+
+        let stops = self.solids.iter()
+            .filter(|s| blocks(s, min, max))
+            .map(|s| {
+                if is_forward { s.min[axis] - dim[axis] } else { s.max[axis] }
+            });
+
+        if is_forward { stops.reduce(f32::min) } else { stops.reduce(f32::max) }
+    }
+
+    /// where a ray from `origin` along the unit vector `dir` first hits the
+    /// ground or a solid, within `max_dist`
+    pub fn ray_cast(&self, origin: Vec3, dir: Vec3, max_dist: f32)
+        -> Option<RayHit>
+    {
+        let ground = self.ground_ray_hit(origin, dir, max_dist);
+        let solids = self.solids.iter()
+            .enumerate()
+            .filter_map(|(i, s)| {
+                let (dist, normal) = s.ray_hit(origin, dir)?;
+
+                Some(RayHit::new(Target::Solid(i), origin, dir, dist, normal))
+            })
+            .filter(|h| h.dist <= max_dist);
+
+        ray::nearest(solids.chain(ground))
+    }
+
+    /// whether nothing of the terrain is in the way between `from` and `to`
+    pub fn has_line_of_sight(&self, from: Vec3, to: Vec3) -> bool {
+        let offset = to - from;
+        let dist = offset.length();
+
+        dist == 0.0 || self.ray_cast(from, offset / dist, dist).is_none()
+    }
+}
+
+impl Terrain {
+    fn generate_islands(&mut self, seed: u64) {
+        let mut rng = Rng::new(seed ^ ISLAND_STREAM);
+
+        self.islands = self.island_settings.map(|s| {
+            s.generate(self.width, &self.anchors, &mut rng)
+        });
+    }
+
+    /// height and normal of the highest surface below a box at `pos` with
+    /// dimensions `dim`, if any
+    fn ground(&self, pos: Vec3, dim: Vec3) -> Option<(f32, Vec3)> {
+        let (min, max) = (pos, pos + dim);
+        let floor = self.floor_height(pos, dim).map(|height| {
+            let centre = pos + dim / 2.0;
+            let normal = self.heightmap.as_ref()
+                .map_or(Vec3::Y, |m| m.normal_at(centre.x, centre.z));
+
+            (height, normal)
+        });
+
+        self.solids.iter()
+            .filter(|s| s.overlaps_xz(min, max))
+            .map(|s| (s.top_over(min, max), s.normal()))
+            .filter(|&(top, _)| top <= pos.y + STEP_HEIGHT)
+            .chain(floor)
+            .reduce(|a, b| if b.0 > a.0 { b } else { a })
+    }
+
+    /// where a ray from `origin` along the unit vector `dir` first goes
+    /// below the terrain itself, within `max_dist`, ignoring solids
+    fn ground_ray_hit(&self, origin: Vec3, dir: Vec3, max_dist: f32)
+        -> Option<RayHit>
+    {
+        let is_land = |x, z| self.is_land(x, z);
+
+        let (dist, normal) = match &self.heightmap {
+            Some(map) => {
+                let dist = map.ray_hit(origin, dir, max_dist, is_land)?;
+                let pos = origin + dir * dist;
+
+                (dist, map.normal_at(pos.x, pos.z))
+            }
+            None => {
+                if origin.y < 0.0 || dir.y >= 0.0 { return None }
+
+                let dist = -origin.y / dir.y;
+                let pos = origin + dir * dist;
+
+                if dist > max_dist || !is_land(pos.x, pos.z) { return None }
+
+                (dist, Vec3::Y)
+            }
+        };
+
+        Some(RayHit::new(Target::Ground, origin, dir, dist, normal))
+    }
+
+    /// height of the terrain itself below a box at `pos` with dimensions
+    /// `dim`, ignoring solids, unless it is beyond the edge
+    ///
+    /// the box rests on the highest of its corners
+    fn floor_height(&self, pos: Vec3, dim: Vec3) -> Option<f32> {
+        if self.is_outside_bounds(pos) { return None }
+
+        let Some(map) = &self.heightmap else { return Some(0.0) };
+        let corners = [
+            (pos.x, pos.z),
+            (pos.x + dim.x, pos.z),
+            (pos.x, pos.z + dim.z),
+            (pos.x + dim.x, pos.z + dim.z)
+        ];
+
+        corners.into_iter()
+            .map(|(x, z)| map.height_at(x, z))
+            .reduce(f32::max)
+    }
+}
+
+impl WallSettings {
+    /// defaults that suit a terrain of the given width
+    pub fn for_width(width: f32) -> Self {
+        let size = width * 2.0;
+
+        Self {
+            size,
+            shake_coeff: 35.0,
+            stretch_coeff: 90.0,
+            max_offset: 5.0,
+            dim_limit: (size * 0.8, size * 1.2)
+        }
+    }
+}
+
+impl Terrain {
+    fn update_grid(&mut self, player: &Player) {
+        self.grid_colors.0 = if player.is_sprinting {
+            YELLOW
+        } else {
+            BLACK
+        };
+    }
+
+    fn update_wall(&mut self, player: &Player, rng: &mut Rng, time_delta: f32) {
+        let player_pos = player.pos();
+
+        // Do nothing if the player is victorious (and can therefore fly).
+        if player.is_victorious {
+            self.wall_color = WHITE;
+        } else if self.is_outside_bounds(player_pos) {
+            self.wall_color = RED;
+        // Dynamic Feline Stress Factor (DFSF).
+        } else {
+            self.wall_color = WHITE;
+
+            // Halfway to the edge of the terrain.
+            let halfway = self.width / 4.0;
+            let dist_beyond = lateral_distance(player_pos) - halfway;
+
+            if dist_beyond > 0.0 {
+                let shift_speed = dist_beyond / halfway * time_delta;
+
+                self.shake_wall(rng, shift_speed);
+                self.stretch_wall(rng, shift_speed);
+            }
+        }
+    }
+}
+
+impl Terrain {
+    fn shake_wall(&mut self, rng: &mut Rng, speed: f32) {
+        let coords = [
+            &mut self.wall_pos.x,
+            &mut self.wall_pos.y,
+            &mut self.wall_pos.z
+        ];
+
+        for c in coords {
+            let offset = rng.sign(speed) * self.wall.shake_coeff;
+            let limit = self.wall.max_offset;
+
+            *c = (*c + offset).clamp(-limit, limit);
+        }
+    }
+
+    fn stretch_wall(&mut self, rng: &mut Rng, speed: f32) {
+        let coords = [
+            &mut self.wall_dim.x,
+            &mut self.wall_dim.y,
+            &mut self.wall_dim.z
+        ];
+
+        for c in coords {
+            let offset = rng.sign(speed) * self.wall.stretch_coeff;
+            let (min, max) = self.wall.dim_limit;
+
+            *c = (*c + offset).clamp(min, max);
+        }
+    }
+}
+
+/// whether `solid` stands in the way of a box between the corners `min` and
+/// `max` moving sideways, rather than being low enough to step onto
+fn blocks(solid: &Solid, min: Vec3, max: Vec3) -> bool {
+    solid.overlaps_xz(min, max)
+        && max.y > solid.min.y + GROUND_TOLERANCE
+        && solid.top_over(min, max) > min.y + STEP_HEIGHT
+}
+
+/// from origin
+///
+/// distance from origin to `pos` projected on whichever axis gives a larger
+/// distance
+fn lateral_distance(pos: Vec3) -> f32 {
+    let [x, _, z] = pos.abs().to_array();
+
+    x.max(z)
+}
@@ -0,0 +1,75 @@
+use super::{World, TICK};
+use super::collision;
+use super::mob::Mob;
+use super::mob::ball::Ball;
+
+use macroquad::prelude::*;
+
+/// Longest flight predicted, in seconds.
+const MAX_FLIGHT: f32 = 4.0;
+
+/// the predicted flight of a ball, up to where it first hits something
+pub struct Trajectory {
+    /// Centres of the ball at every tick, from where it starts.
+    pub points: Vec<Vec3>,
+    /// None if the ball flies for too long, or falls into the void.
+    pub end: Option<Impact>
+}
+
+#[derive(Clone, Copy)]
+pub enum Impact {
+    /// Where the bottom of the ball lands.
+    Ground(Vec3),
+    /// Index of the frog.
+    Frog(usize)
+}
+
+impl Trajectory {
+    /// simulates `ball` like [`World::step`] does, with frogs standing still
+    pub fn predict(world: &World, mut ball: Ball) -> Self {
+        let terrain = &world.terrain;
+        let mut points = vec![ball.centre()];
+
+        for _ in 0..(MAX_FLIGHT / TICK) as u32 {
+            ball.update(terrain, &world.frogs, TICK);
+            points.push(ball.centre());
+
+            if let Some(i) = first_frog_hit(world, &ball) {
+                return Self { points, end: Some(Impact::Frog(i)) }
+            }
+
+            if ball.is_on_ground(terrain) {
+                let bottom = ball.pos() + ball.dim() * vec3(0.5, 0.0, 0.5);
+
+                return Self { points, end: Some(Impact::Ground(bottom)) }
+            }
+
+            if ball.is_expired() { break }
+        }
+
+        Self { points, end: None }
+    }
+}
+
+/// index of the frog `ball` hit first since the previous tick, if any
+fn first_frog_hit(world: &World, ball: &Ball) -> Option<usize> {
+    let motion = ball.pos() - ball.prev_pos();
+
+    world.frogs.iter()
+        .enumerate()
+        .filter_map(|(i, f)| {
+            let is_touching = collision::box_contact(
+                ball.pos(), ball.dim(), f.pos(), f.dim()
+            ).is_some();
+
+            if is_touching { return Some((i, 1.0)) }
+
+            let (time, _) = collision::sweep_boxes(
+                ball.prev_pos(), ball.dim(), motion, f.pos(), f.dim()
+            )?;
+
+            Some((i, time))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+}
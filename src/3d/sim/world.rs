@@ -0,0 +1,631 @@
+use super::{Input, Rng};
+use super::level::Level;
+use super::rules::{Rule, Status};
+use super::terrain::Terrain;
+use super::zone::Zone;
+use super::collision::{self, collide, collide_swept};
+use super::broadphase::{self, Grid};
+use super::save::{self, Snapshot, SaveError};
+use super::mob::Mob;
+use super::mob::{player::Player, frog::Frog, ball::Ball, gust::Gust};
+use super::mob::player::Thrown;
+use super::mob::frog::Senses;
+use super::mob::flock::Flock;
+use super::trajectory::Trajectory;
+use super::ray::{self, RayHit, Target};
+
+use macroquad::prelude::*;
+
+use ringbuf::Rb;
+
+use ringbuf::StaticRb as RingBuf;
+
+const BALLS_MAX: usize = 50;    // limits memory usage
+/// Furthest a resting ball can be picked up from the eyes, in meters.
+const PICK_UP_REACH: f32 = 3.0;
+/// Resting balls this close to the player, in meters, are picked up.
+const PICK_UP_MARGIN: f32 = 0.2;
+/// Furthest a frog can be grabbed from the eyes, in meters.
+const GRAB_REACH: f32 = 6.0;
+
+pub struct World {
+    pub terrain: Terrain,
+    pub player: Player,
+    pub frogs: Vec<Frog>,
+    pub balls: RingBuf<Ball, BALLS_MAX>,
+    pub gusts: Vec<Gust>,
+    flock: Option<Flock>,
+
+    win: Rule,
+    /// Progress towards `win`, as of the last step.
+    goals: Status,
+    /// Simulated seconds since the start.
+    time: f32,
+    /// Times each frog was struck by a ball.
+    hits: Vec<u32>,
+    seed: u64,
+    rng: Rng,
+
+    hazards: Vec<Zone>,
+    checkpoints: Vec<Zone>,
+    /// Where the player respawns.
+    checkpoint: Vec3,
+    lives: u32,
+    /// Balls the player can throw, unlimited if none.
+    ammo: Option<u32>,
+    /// Index of the frog the player carries.
+    carried: Option<usize>,
+    is_player_dead: bool,
+
+    /// Rebuilt every tick, only kept to reuse its memory.
+    grid: Grid
+}
+
+/// things that happened during a step, for the front end to react to
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event {
+    Throw,
+    /// The player picked up a ball.
+    PickUp,
+    /// A frog croaked, on its own or when struck by a ball.
+    Croak,
+    /// The player reached a new checkpoint.
+    Checkpoint,
+    Death
+}
+
+/// a mob of the world, identified by a single index for the broadphase
+///
+/// the player comes first, then frogs, then balls
+#[derive(Clone, Copy)]
+enum MobId {
+    Player,
+    Frog(usize),
+    Ball(usize)
+}
+
+impl World {
+    /// runs with the same `seed` and input always play out the same way
+    pub fn new(level: &Level, seed: u64) -> Self {
+        let mut world = Self {
+            terrain: Terrain::new(level, seed),
+            player: Player::new(level.spawn),
+            frogs: level.frogs.iter().map(Frog::new).collect(),
+            balls: RingBuf::default(),
+            gusts: Vec::new(),
+            flock: level.flock,
+
+            win: level.win.clone(),
+            goals: Status::default(),
+            time: 0.0,
+            hits: vec![0; level.frogs.len()],
+            seed,
+            rng: Rng::new(seed),
+
+            hazards: level.hazards.clone(),
+            checkpoints: level.checkpoints.clone(),
+            checkpoint: level.spawn,
+            lives: level.lives,
+            ammo: level.ammo,
+            carried: None,
+            is_player_dead: false,
+
+            grid: Grid::default()
+        };
+
+        world.goals = world.win.eval(&world);
+        world
+    }
+
+    pub fn goals(&self) -> &Status { &self.goals }
+    pub fn time(&self) -> f32 { self.time }
+    pub fn seed(&self) -> u64 { self.seed }
+
+    /// by balls, on the frog at `index`
+    pub fn hits(&self, index: usize) -> u32 { self.hits[index] }
+
+    pub fn hazards(&self) -> &[Zone] { &self.hazards }
+    pub fn checkpoints(&self) -> &[Zone] { &self.checkpoints }
+    pub fn checkpoint(&self) -> Vec3 { self.checkpoint }
+
+    /// remaining, including the current one
+    pub fn lives(&self) -> u32 { self.lives }
+    /// balls the player can throw, unlimited if none
+    pub fn ammo(&self) -> Option<u32> { self.ammo }
+    /// index of the frog the player carries, if any
+    pub fn carried(&self) -> Option<usize> { self.carried }
+
+    /// where the ball the player is charging would go if thrown now, unless
+    /// they are about to throw a gust
+    pub fn predict_throw(&self) -> Option<Trajectory> {
+        match self.player.aim() {
+            Thrown::Ball(ball) => Some(Trajectory::predict(self, ball)),
+            Thrown::Gust(_) => None
+        }
+    }
+
+    /// where a ray from `origin` along the unit vector `dir` first hits a
+    /// mob, the ground or a solid, within `max_dist`
+    ///
+    /// mobs the ray starts inside of are not hit, so rays can be cast from
+    /// the eyes of the player. gusts and balls stuck to frogs are not hit
+    /// either
+    pub fn ray_cast(&self, origin: Vec3, dir: Vec3, max_dist: f32)
+        -> Option<RayHit>
+    {
+        let player = (!self.is_player_dead)
+            .then(|| (Target::Player, self.player.pos(), self.player.dim()));
+        let frogs = self.frogs.iter()
+            .enumerate()
+            .map(|(i, f)| (Target::Frog(i), f.pos(), f.dim()));
+        let balls = self.balls.iter()
+            .enumerate()
+            .filter(|(_, b)| b.stuck_to().is_none())
+            .map(|(i, b)| (Target::Ball(i), b.pos(), b.dim()));
+
+        let mobs = player.into_iter()
+            .chain(frogs)
+            .chain(balls)
+            .filter_map(|(target, pos, dim)| {
+                let (dist, normal) =
+                    collision::ray_box(origin, dir, pos, dim)?;
+
+                (dist > 0.0 && dist <= max_dist)
+                    .then(|| RayHit::new(target, origin, dir, dist, normal))
+            });
+
+        ray::nearest(mobs.chain(self.terrain.ray_cast(origin, dir, max_dist)))
+    }
+
+    /// what the player looks at, within `reach`
+    pub fn aimed_at(&self, reach: f32) -> Option<RayHit> {
+        self.ray_cast(self.player.eye(), self.player.front(), reach)
+    }
+
+    /// index of the frog the player would grab, if any
+    pub fn grabbable_frog(&self) -> Option<usize> {
+        match self.aimed_at(GRAB_REACH)?.target {
+            Target::Frog(i) => Some(i),
+            _ => None
+        }
+    }
+
+    /// the player stays dead until a step with [`Input::respawn`], and
+    /// ignores any other input
+    pub fn is_player_dead(&self) -> bool { self.is_player_dead }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            version: save::VERSION,
+
+            seed: save::to_hex(self.seed),
+            rng: save::to_hex(self.rng.state()),
+
+            time: self.time,
+            hits: self.hits.clone(),
+            checkpoint: self.checkpoint.into(),
+            lives: self.lives,
+            ammo: self.ammo,
+            carried: self.carried,
+            is_player_dead: self.is_player_dead,
+
+            terrain: self.terrain.snapshot(),
+            player: self.player.snapshot(),
+            frogs: self.frogs.iter().map(Frog::snapshot).collect(),
+            balls: self.balls.iter().map(Ball::snapshot).collect(),
+            gusts: self.gusts.iter().map(Gust::snapshot).collect()
+        }
+    }
+
+    /// the world must have been created from the same level as `snap`
+    pub fn restore(&mut self, snap: &Snapshot) -> Result<(), SaveError> {
+        let frog_count = self.frogs.len();
+
+        if snap.frogs.len() != frog_count || snap.hits.len() != frog_count {
+            return Err(SaveError::Mismatch(format!(
+                "expected {frog_count} frogs, found {}", snap.frogs.len()
+            )))
+        }
+
+        // Dying takes a life, so the living always have one left.
+        if !snap.is_player_dead && snap.lives == 0 {
+            let msg = "a living player must have lives left".to_string();
+
+            return Err(SaveError::Invalid(msg))
+        }
+
+        let stuck_to = snap.balls.iter().filter_map(|b| b.stuck_to);
+
+        if let Some(i) = stuck_to.filter(|&i| i >= frog_count).max() {
+            return Err(SaveError::Mismatch(format!(
+                "ball stuck to frog {i}, but there are {frog_count} frogs"
+            )))
+        }
+
+        // Collisions divide by masses.
+        let mut masses = snap.frogs.iter().map(|f| f.mass)
+            .chain(snap.balls.iter().map(|b| b.mass));
+
+        if masses.any(|m| !m.is_finite() || m <= 0.0) {
+            let msg = "masses must be positive".to_string();
+
+            return Err(SaveError::Invalid(msg))
+        }
+
+        let seed = save::parse_hex(&snap.seed, "seed")?;
+        let rng = save::parse_hex(&snap.rng, "rng")?;
+
+        self.time = snap.time;
+        self.seed = seed;
+        self.rng = Rng::from_state(rng);
+        self.hits.clone_from(&snap.hits);
+        self.checkpoint = snap.checkpoint.into();
+        self.lives = snap.lives;
+        self.ammo = snap.ammo;
+        self.carried = snap.carried.filter(|&i| i < frog_count);
+        self.is_player_dead = snap.is_player_dead;
+
+        self.terrain.restore(&snap.terrain, seed);
+        self.player = Player::restore(&snap.player);
+        self.frogs = snap.frogs.iter().map(Frog::restore).collect();
+
+        self.balls.clear();
+
+        for b in &snap.balls {
+            self.balls.push_overwrite(Ball::restore(b));
+        }
+
+        self.gusts = snap.gusts.iter().map(Gust::restore).collect();
+
+        self.goals = self.win.eval(self);
+
+        Ok(())
+    }
+
+    /// advances the simulation by `time_delta` seconds
+    ///
+    /// `time_delta` should be constant for reproducible results (see
+    /// [`super::TICK`])
+    pub fn step(&mut self, input: &Input, time_delta: f32) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        self.time += time_delta;
+
+        if !self.is_player_dead {
+            self.apply_input(input, time_delta, &mut events);
+        } else if input.respawn {
+            self.respawn();
+        }
+
+        let terrain = &self.terrain;
+        let threats = self.frog_threats();
+        // Only needed for flocking.
+        let herd: Vec<_> = if self.flock.is_some() {
+            self.frogs.iter().map(|f| (f.centre(), f.vel())).collect()
+        } else {
+            Vec::new()
+        };
+        let senses = Senses {
+            threats: &threats,
+            frogs: &herd,
+            flock: self.flock.as_ref()
+        };
+
+        self.player.update(terrain, time_delta);
+
+        let carry_point = self.player.carry_point();
+
+        for (i, f) in self.frogs.iter_mut().enumerate() {
+            if self.carried == Some(i) {
+                f.carry(terrain, carry_point, time_delta);
+            } else if f.update(terrain, senses, &mut self.rng, time_delta) {
+                events.push(Event::Croak);
+            }
+        }
+
+        for b in self.balls.iter_mut() {
+            b.update(terrain, &self.frogs, time_delta);
+        }
+
+        self.despawn_balls();
+        self.blow_gusts(time_delta);
+
+        if !self.is_player_dead {
+            self.pick_up_balls(input.pick_up, &mut events);
+        }
+
+        self.resolve_collisions(&mut events);
+
+        self.terrain.update(&self.player, &mut self.rng, time_delta);
+
+        self.goals = self.win.eval(self);
+        if self.goals.is_met { self.player.is_victorious = true }
+
+        if !self.is_player_dead { self.update_checkpoints(&mut events) }
+
+        events
+    }
+}
+
+impl World {
+    /// pushes frogs and free balls, then removes gusts that died down
+    fn blow_gusts(&mut self, time_delta: f32) {
+        for g in &mut self.gusts {
+            g.update(time_delta);
+
+            for f in &mut self.frogs { g.blow(f, time_delta) }
+
+            for b in self.balls.iter_mut() {
+                if b.stuck_to().is_none() { g.blow(b, time_delta) }
+            }
+        }
+
+        self.gusts.retain(|g| !g.is_expired());
+    }
+
+    fn despawn_balls(&mut self) {
+        let expired: Vec<usize> = self.balls.iter()
+            .enumerate()
+            .filter(|(_, b)| b.is_expired())
+            .map(|(i, _)| i)
+            .collect();
+
+        self.remove_balls(&expired);
+    }
+
+    /// resting balls the player walks over, and the one they look at if
+    /// `is_reaching`, unless ammo is unlimited
+    fn pick_up_balls(&mut self, is_reaching: bool, events: &mut Vec<Event>) {
+        let Some(ammo) = self.ammo else { return };
+        let player = &self.player;
+        let margin = Vec3::splat(PICK_UP_MARGIN);
+        let mut picked: Vec<usize> = self.balls.iter()
+            .enumerate()
+            .filter(|(_, b)| b.is_resting() && collision::box_contact(
+                player.pos() - margin, player.dim() + margin * 2.0,
+                b.pos(), b.dim()
+            ).is_some())
+            .map(|(i, _)| i)
+            .collect();
+
+        let aimed = is_reaching.then(|| self.aimed_at(PICK_UP_REACH));
+        let looked_at = match aimed {
+            Some(Some(RayHit { target: Target::Ball(i), .. })) => Some(i),
+            _ => None
+        };
+
+        if let Some(i) = looked_at {
+            let is_resting = self.balls.iter().nth(i)
+                .is_some_and(Ball::is_resting);
+
+            if is_resting && !picked.contains(&i) { picked.push(i) }
+        }
+
+        events.extend(picked.iter().map(|_| Event::PickUp));
+        self.ammo = Some(ammo + picked.len() as u32);
+
+        self.remove_balls(&picked);
+    }
+
+    /// keeps the other balls in order
+    fn remove_balls(&mut self, indices: &[usize]) {
+        if indices.is_empty() { return }
+
+        let kept: Vec<Ball> = self.balls.pop_iter()
+            .enumerate()
+            .filter(|(i, _)| !indices.contains(i))
+            .map(|(_, b)| b)
+            .collect();
+
+        for b in kept { self.balls.push_overwrite(b); }
+    }
+
+    /// revives the player at the last checkpoint, if they have lives left
+    fn respawn(&mut self) {
+        if self.lives == 0 { return }
+
+        let is_victorious = self.player.is_victorious;
+        let projectile = self.player.projectile();
+
+        self.player = Player::new(self.checkpoint);
+        self.player.is_victorious = is_victorious;
+        self.player.select(projectile);
+        self.is_player_dead = false;
+    }
+
+    /// centres of the living player and of moving balls, except those glued
+    /// to frogs
+    fn frog_threats(&self) -> Vec<Vec3> {
+        // Balls slower than this, in meters per second, are harmless.
+        const MIN_BALL_SPEED: f32 = 1.0;
+
+        let player = (!self.is_player_dead).then(|| self.player.centre());
+        let balls = self.balls.iter()
+            .filter(|b| b.stuck_to().is_none())
+            .filter(|b| b.vel().length() > MIN_BALL_SPEED)
+            .map(Ball::centre);
+
+        player.into_iter().chain(balls).collect()
+    }
+
+    /// separates every pair of overlapping mobs
+    fn resolve_collisions(&mut self, events: &mut Vec<Event>) {
+        let Self { terrain, player, frogs, balls, hits, grid, flock, .. } =
+            self;
+        let mut balls: Vec<&mut Ball> = balls.iter_mut().collect();
+        let frog_count = frogs.len();
+
+        grid.clear();
+        insert_swept(grid, 0, &*player);
+
+        for (i, f) in frogs.iter().enumerate() {
+            insert_swept(grid, 1 + i, f);
+        }
+
+        // Balls glued to frogs just follow them.
+        for (i, b) in balls.iter().enumerate() {
+            if b.stuck_to().is_none() {
+                insert_swept(grid, 1 + frog_count + i, &**b);
+            }
+        }
+
+        for &(a, b) in grid.pairs() {
+            use MobId::*;
+
+            match (MobId::new(a, frog_count), MobId::new(b, frog_count)) {
+                (Player, Frog(i)) => {
+                    collide(player, &mut frogs[i], terrain);
+                }
+                (Player, Ball(i)) => {
+                    let ball = &mut *balls[i];
+
+                    // Let thrown balls leave the hand.
+                    if !ball.overlapped(player) {
+                        collide_swept(ball, player, terrain);
+                    }
+                }
+                (Frog(i), Frog(j)) => {
+                    let (a, b) = broadphase::pair_mut(frogs, i, j);
+
+                    collide(a, b, terrain);
+                }
+                (Frog(i), Ball(j)) => {
+                    let (frog, ball) = (&mut frogs[i], &mut *balls[j]);
+                    let was_touching = ball.intersected(frog);
+
+                    // Only count the first tick of contact.
+                    let is_hit = collide_swept(ball, frog, terrain).is_some();
+
+                    if is_hit && !was_touching {
+                        hits[i] += 1;
+                        events.push(Event::Croak);
+                        ball.strike(i, frog);
+
+                        if let Some(f) = flock { frog.scatter(f.scatter_time) }
+                    }
+                }
+                (Ball(i), Ball(j)) => {
+                    let (a, b) = broadphase::pair_mut(&mut balls, i, j);
+
+                    collide(*a, *b, terrain);
+                }
+                _ => unreachable!("pairs should list the lowest id first")
+            }
+        }
+    }
+
+    fn update_checkpoints(&mut self, events: &mut Vec<Event>) {
+        let player = &self.player;
+        let is_in_hazard = self.hazards.iter().any(|h| h.touches(player));
+
+        if player.is_in_void(&self.terrain) || is_in_hazard {
+            self.lives -= 1;
+            self.is_player_dead = true;
+            self.carried = None;
+            events.push(Event::Death);
+
+            return
+        }
+
+        let reached = self.checkpoints.iter()
+            .find(|c| c.touches(player))
+            .map(Zone::floor);
+
+        if let Some(pos) = reached {
+            if pos != self.checkpoint {
+                self.checkpoint = pos;
+                events.push(Event::Checkpoint);
+            }
+        }
+    }
+
+    fn apply_input(
+        &mut self,
+        input: &Input,
+        time_delta: f32,
+        events: &mut Vec<Event>
+    ) {
+        let player = &mut self.player;
+
+        if input.look != Vec2::ZERO {
+            player.look(input.look);
+        }
+
+        if let Some(kind) = input.zoom { player.zoom(kind, time_delta) }
+
+        player.is_sprinting = input.is_sprinting;
+
+        if let Some(p) = input.select { player.select(p) }
+
+        // Letting go without throwing, as when pausing, loses the charge.
+        if input.is_charging {
+            player.charge_throw(time_delta);
+        } else if !input.throw {
+            player.drop_charge();
+        }
+
+        self.update_grab(input);
+
+        if input.throw { self.throw(events) }
+
+        let player = &mut self.player;
+
+        // Don't walk on the air, unless you deserve it...
+        if !player.can_move(&self.terrain) { return }
+
+        for &dir in &input.moves { player.accel(dir, time_delta) }
+
+        if input.jump { player.jump() }
+        if input.super_leap { player.super_leap(time_delta) }
+    }
+
+    /// starts carrying the frog being looked at, or drops the carried frog
+    fn update_grab(&mut self, input: &Input) {
+        if !input.is_grabbing {
+            self.carried = None;
+        } else if input.grab && self.carried.is_none() {
+            self.carried = self.grabbable_frog();
+        }
+    }
+
+    /// launches the carried frog, or else whatever is selected
+    fn throw(&mut self, events: &mut Vec<Event>) {
+        if let Some(i) = self.carried.take() {
+            self.frogs[i].set_vel(self.player.launch());
+            events.push(Event::Throw);
+
+            return
+        }
+
+        // The charge is lost even without anything to throw. gusts are not
+        // balls, so they need no ammo.
+        match self.player.throw() {
+            Thrown::Ball(_) if self.ammo == Some(0) => return,
+            Thrown::Ball(b) => {
+                self.balls.push_overwrite(b);
+
+                if let Some(ammo) = &mut self.ammo { *ammo -= 1 }
+            }
+            Thrown::Gust(g) => self.gusts.push(g)
+        }
+
+        events.push(Event::Throw);
+    }
+}
+
+impl MobId {
+    fn new(id: u32, frog_count: usize) -> Self {
+        match id as usize {
+            0 => Self::Player,
+            i if i <= frog_count => Self::Frog(i - 1),
+            i => Self::Ball(i - 1 - frog_count)
+        }
+    }
+}
+
+/// covering where the mob was at the previous tick, for swept collisions
+fn insert_swept<M: Mob>(grid: &mut Grid, id: usize, mob: &M) {
+    let (pos, prev_pos) = (mob.pos(), mob.prev_pos());
+
+    grid.insert(id as u32, pos.min(prev_pos), pos.max(prev_pos) + mob.dim());
+}
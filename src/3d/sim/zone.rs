@@ -0,0 +1,51 @@
+use super::mob::{Mob, boxes_intersect};
+
+use macroquad::prelude::*;
+
+use serde::Deserialize;
+
+/// an axis-aligned box in the world
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "ZoneDef")]
+pub struct Zone {
+    pub min: Vec3,
+    pub max: Vec3
+}
+
+impl Zone {
+    pub fn dim(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    /// centre of the bottom face, where mobs can stand
+    pub fn floor(&self) -> Vec3 {
+        let centre = (self.min + self.max) / 2.0;
+
+        vec3(centre.x, self.min.y, centre.z)
+    }
+
+    pub fn touches<M: Mob>(&self, mob: &M) -> bool {
+        boxes_intersect(self.min, self.dim(), mob.pos(), mob.dim())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ZoneDef {
+    min: [f32; 3],
+    max: [f32; 3]
+}
+
+impl TryFrom<ZoneDef> for Zone {
+    type Error = &'static str;
+
+    fn try_from(def: ZoneDef) -> Result<Self, Self::Error> {
+        let (min, max) = (Vec3::from(def.min), Vec3::from(def.max));
+
+        if min.is_finite() && max.is_finite() && min.cmple(max).all() {
+            Ok(Self { min, max })
+        } else {
+            Err("zone must have min <= max on every axis")
+        }
+    }
+}
@@ -0,0 +1,228 @@
+//! maps the actions of a game to keys and mouse buttons
+//!
+//! shared by both games, each with its own action enum and bindings file
+
+use macroquad::prelude::*;
+
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+use std::collections::BTreeMap;
+use std::{fmt, fs, io};
+use std::path::Path;
+
+/// Keys that can be bound, also used to look them up by name.
+const KEYS: &[KeyCode] = {
+    use KeyCode::*;
+
+    &[
+        A, B, C, D, E, F, G, H, I, J, K, L, M,
+        N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        Key0, Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        Space, Enter, Tab, Backspace, Escape,
+        Insert, Delete, Home, End, PageUp, PageDown,
+        Up, Down, Left, Right,
+        LeftShift, LeftControl, LeftAlt, LeftSuper,
+        RightShift, RightControl, RightAlt, RightSuper,
+        Apostrophe, Comma, Minus, Period, Slash, Semicolon, Equal,
+        LeftBracket, Backslash, RightBracket, GraveAccent,
+        CapsLock, ScrollLock, NumLock, PrintScreen, Pause, Menu,
+        Kp0, Kp1, Kp2, Kp3, Kp4, Kp5, Kp6, Kp7, Kp8, Kp9,
+        KpDecimal, KpDivide, KpMultiply, KpSubtract, KpAdd, KpEnter, KpEqual
+    ]
+};
+
+const BUTTONS: &[(MouseButton, &str)] = &[
+    (MouseButton::Left, "MouseLeft"),
+    (MouseButton::Right, "MouseRight"),
+    (MouseButton::Middle, "MouseMiddle")
+];
+
+/// a key or mouse button, written by name in bindings files (e.g. "W",
+/// "LeftShift", "MouseLeft")
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton)
+}
+
+/// the bindings of every action, falling back to defaults for any action a
+/// file leaves out
+#[derive(Clone)]
+pub struct Bindings<A> {
+    map: BTreeMap<A, Vec<Binding>>,
+    defaults: BTreeMap<A, Vec<Binding>>
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(io::Error),
+    Read(toml::de::Error),
+    Write(toml::ser::Error)
+}
+
+impl Binding {
+    pub fn is_down(self) -> bool {
+        match self {
+            Self::Key(key) => is_key_down(key),
+            Self::Mouse(btn) => is_mouse_button_down(btn)
+        }
+    }
+
+    pub fn is_pressed(self) -> bool {
+        match self {
+            Self::Key(key) => is_key_pressed(key),
+            Self::Mouse(btn) => is_mouse_button_pressed(btn)
+        }
+    }
+
+    pub fn is_released(self) -> bool {
+        match self {
+            Self::Key(key) => is_key_released(key),
+            Self::Mouse(btn) => is_mouse_button_released(btn)
+        }
+    }
+
+    /// whichever bindable key or button was pressed this frame, if any
+    pub fn last_pressed() -> Option<Self> {
+        let key = get_last_key_pressed()
+            .filter(|k| KEYS.contains(k))
+            .map(Self::Key);
+
+        key.or_else(|| {
+            BUTTONS.iter()
+                .map(|&(btn, _)| btn)
+                .find(|&btn| is_mouse_button_pressed(btn))
+                .map(Self::Mouse)
+        })
+    }
+}
+
+impl<A: Copy + Ord> Bindings<A> {
+    pub fn new(defaults: impl IntoIterator<Item = (A, Vec<Binding>)>) -> Self {
+        let defaults: BTreeMap<_, _> = defaults.into_iter().collect();
+
+        Self { map: defaults.clone(), defaults }
+    }
+
+    /// held by any of its bindings
+    pub fn is_down(&self, action: A) -> bool {
+        self.get(action).iter().any(|b| b.is_down())
+    }
+
+    /// pressed this frame by any of its bindings
+    pub fn is_pressed(&self, action: A) -> bool {
+        self.get(action).iter().any(|b| b.is_pressed())
+    }
+
+    /// released this frame by any of its bindings
+    pub fn is_released(&self, action: A) -> bool {
+        self.get(action).iter().any(|b| b.is_released())
+    }
+
+    pub fn get(&self, action: A) -> &[Binding] {
+        self.map.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn set(&mut self, action: A, bindings: Vec<Binding>) {
+        self.map.insert(action, bindings);
+    }
+
+    pub fn reset(&mut self, action: A) {
+        let bindings = self.defaults.get(&action).cloned().unwrap_or_default();
+
+        self.map.insert(action, bindings);
+    }
+
+    /// names of the bindings of `action`, or "none"
+    pub fn describe(&self, action: A) -> String {
+        let names: Vec<_> = self.get(action).iter()
+            .map(Binding::to_string)
+            .collect();
+
+        if names.is_empty() { "none".to_string() } else { names.join(", ") }
+    }
+
+    /// overrides the defaults with the actions listed in `keys`
+    pub fn with_keys(mut self, keys: BTreeMap<A, Vec<Binding>>) -> Self {
+        self.map.extend(keys);
+        self
+    }
+
+    /// what a bindings file should contain
+    pub fn keys(&self) -> &BTreeMap<A, Vec<Binding>> {
+        &self.map
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Key(key) => write!(f, "{key:?}"),
+            Self::Mouse(btn) => {
+                let name = BUTTONS.iter()
+                    .find(|(b, _)| b == btn)
+                    .map_or("MouseUnknown", |(_, name)| name);
+
+                write!(f, "{name}")
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        let key = KEYS.iter()
+            .find(|k| format!("{k:?}").eq_ignore_ascii_case(&name))
+            .map(|&k| Self::Key(k));
+        let btn = || BUTTONS.iter()
+            .find(|(_, n)| n.eq_ignore_ascii_case(&name))
+            .map(|&(b, _)| Self::Mouse(b));
+
+        key.or_else(btn)
+            .ok_or_else(|| format!("unknown key or button \"{name}\""))
+    }
+}
+
+impl From<Binding> for String {
+    fn from(binding: Binding) -> Self {
+        binding.to_string()
+    }
+}
+
+/// `None` if the file does not exist
+pub fn read_file<T>(path: &Path) -> Result<Option<T>, BindingsError>
+    where T: DeserializeOwned
+{
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(BindingsError::Io(e))
+    };
+
+    toml::from_str(&text).map(Some).map_err(BindingsError::Read)
+}
+
+pub fn write_file<T>(val: &T, path: &Path) -> Result<(), BindingsError>
+    where T: Serialize
+{
+    let text = toml::to_string(val).map_err(BindingsError::Write)?;
+
+    fs::write(path, text).map_err(BindingsError::Io)
+}
+
+impl fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "cannot access file: {e}"),
+            Self::Read(e) => write!(f, "malformed bindings: {e}"),
+            Self::Write(e) => write!(f, "cannot write bindings: {e}")
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {}
@@ -0,0 +1,5 @@
+//! the 3d simulation, headless so the `3d` front end, tests and benches all
+//! drive the same world
+
+#[path = "3d/sim/mod.rs"]
+pub mod sim;
@@ -0,0 +1,86 @@
+//! the grid that finds which mobs may collide
+
+use game::sim::Rng;
+use game::sim::broadphase::Grid;
+
+use macroquad::prelude::*;
+
+const SEED: u64 = 0xb0c5;
+const BOX_COUNT: u32 = 300;
+/// Boxes are scattered over a cube this wide, in meters.
+const SPREAD: f32 = 40.0;
+
+#[test]
+fn finds_every_overlapping_pair_once() {
+    let mut rng = Rng::new(SEED);
+    let boxes = random_boxes(&mut rng);
+    let pairs = pairs_of(&boxes);
+
+    assert!(pairs.windows(2).all(|w| w[0] < w[1]), "sorted and unique");
+    assert!(pairs.iter().all(|(a, b)| a < b), "lowest id first");
+
+    for (i, &(a, min_a, max_a)) in boxes.iter().enumerate() {
+        for &(b, min_b, max_b) in &boxes[i + 1..] {
+            let is_overlapping = min_a.cmple(max_b).all()
+                && min_b.cmple(max_a).all();
+
+            if is_overlapping {
+                assert!(pairs.contains(&(a.min(b), a.max(b))), "{a} {b}");
+            }
+        }
+    }
+}
+
+#[test]
+fn pairs_large_boxes_with_everything() {
+    let mut rng = Rng::new(SEED);
+    let boxes = random_boxes(&mut rng);
+    let pairs = pairs_of(&boxes);
+    // Much wider than the cells a box may cover.
+    let large = boxes.iter().filter(|(_, min, max)| (*max - *min).x > 10.0);
+
+    for &(a, _, _) in large {
+        for &(b, _, _) in boxes.iter().filter(|(b, _, _)| *b != a) {
+            assert!(pairs.contains(&(a.min(b), a.max(b))), "{a} {b}");
+        }
+    }
+}
+
+#[test]
+fn ignores_insertion_order() {
+    let mut rng = Rng::new(SEED);
+    let boxes = random_boxes(&mut rng);
+    let mut shuffled = boxes.clone();
+
+    // Fisher-Yates.
+    for i in (1..shuffled.len()).rev() {
+        let j = rng.next_u64() as usize % (i + 1);
+
+        shuffled.swap(i, j);
+    }
+
+    assert_eq!(pairs_of(&boxes), pairs_of(&shuffled));
+}
+
+/// ids with the corners of their boxes, mostly mob sized, some of them huge
+/// and overlapping many others
+fn random_boxes(rng: &mut Rng) -> Vec<(u32, Vec3, Vec3)> {
+    (0..BOX_COUNT)
+        .map(|i| {
+            let min = vec3(rng.next_f32(), rng.next_f32(), rng.next_f32())
+                * SPREAD - SPREAD / 2.0;
+            let side = if i % 50 == 7 { 12.0 } else { rng.next_f32() * 3.0 };
+
+            // Ids need not be contiguous.
+            (i * 3 + 1, min, min + Vec3::splat(side))
+        })
+        .collect()
+}
+
+fn pairs_of(boxes: &[(u32, Vec3, Vec3)]) -> Vec<(u32, u32)> {
+    let mut grid = Grid::default();
+
+    for &(id, min, max) in boxes { grid.insert(id, min, max) }
+
+    grid.pairs().to_vec()
+}
@@ -0,0 +1,69 @@
+//! collisions between mobs, including fast ones passing through others
+//! within a single tick
+
+use game::sim::{World, Level, TICK};
+use game::sim::collision;
+use game::sim::level::FrogSpawn;
+use game::sim::mob::{Mob, frog::{self, Frog}, ball::{Ball, Kind}};
+
+use macroquad::prelude::*;
+
+#[test]
+fn sweep_finds_where_a_box_enters_another() {
+    let (min_a, dim_a) = (vec3(-5.0, 0.0, 0.0), Vec3::splat(0.2));
+    let (min_b, dim_b) = (Vec3::ZERO, Vec3::ONE);
+
+    let (time, normal) = collision::sweep_boxes(
+        min_a, dim_a, Vec3::X * 10.0, min_b, dim_b
+    ).unwrap();
+
+    // The front of a, at -4.8, reaches b after 4.8 m.
+    assert!((time - 0.48).abs() < 1e-5, "{time}");
+    assert_eq!(normal, Vec3::X);
+
+    // Passing beside it.
+    let beside = min_a + Vec3::Y * 2.0;
+
+    assert!(collision::sweep_boxes(
+        beside, dim_a, Vec3::X * 10.0, min_b, dim_b
+    ).is_none());
+
+    // Stopping short of it.
+    assert!(collision::sweep_boxes(
+        min_a, dim_a, Vec3::X * 4.0, min_b, dim_b
+    ).is_none());
+
+    // Already overlapping.
+    assert!(collision::sweep_boxes(
+        Vec3::splat(0.5), dim_a, Vec3::X * 10.0, min_b, dim_b
+    ).is_none());
+}
+
+#[test]
+fn swept_collision_catches_a_ball_tunnelling_through_a_frog() {
+    let terrain = World::new(&Level::default(), 0).terrain;
+    let mut frog = Frog::new(&FrogSpawn {
+        pos: Vec3::ZERO,
+        color: GREEN,
+        mass: frog::DEFAULT_MASS,
+        restitution: frog::DEFAULT_RESTITUTION,
+        is_calm: true
+    });
+    // A single tick takes it from one side of the frog to the other.
+    let speed = 3.0 / TICK;
+    let mut ball =
+        Ball::new(vec3(-1.5, 0.4, 0.4), Vec3::X * speed, Kind::Regular);
+
+    ball.update(&terrain, &[], TICK);
+    assert!(ball.pos().x > frog.pos().x + frog.dim().x, "passed through");
+    assert!(collision::collide(&mut ball, &mut frog, &terrain).is_none());
+
+    let contact = collision::collide_swept(&mut ball, &mut frog, &terrain)
+        .unwrap();
+
+    assert_eq!(contact.normal, Vec3::X);
+    // Moved back to where it hit, and bounced off.
+    assert!(ball.pos().x + ball.dim().x <= frog.pos().x + 1e-4);
+    assert!(ball.vel().x < 0.0);
+    assert!(frog.vel().x > 0.0);
+}
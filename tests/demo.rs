@@ -0,0 +1,71 @@
+//! recording runs of the 3d simulation and playing them back
+
+use game::sim::{World, Input, Level, TICK};
+use game::sim::demo::Demo;
+use game::sim::mob::player::{Direction, Projectile};
+
+use macroquad::prelude::*;
+
+use std::{env, fs, process};
+
+const SEED: u64 = 0xde30;
+const TICKS: u32 = 300;
+
+#[test]
+fn replay_matches_the_recording() {
+    let mut world = World::new(&Level::default(), SEED);
+    let mut demo = Demo::new(SEED, None);
+
+    for i in 0..TICKS {
+        let input = busy_input(i);
+
+        demo.record(&input);
+        world.step(&input, TICK);
+    }
+
+    demo.finish(&world);
+
+    // Goes through a file, like a demo recorded by the game.
+    let path = env::temp_dir().join(format!("demo-{}.toml", process::id()));
+
+    demo.write(&path).unwrap();
+
+    let demo = Demo::read(&path);
+
+    fs::remove_file(&path).unwrap();
+
+    let demo = demo.unwrap();
+    let mut replay = World::new(&Level::default(), demo.seed());
+
+    for input in demo.inputs() { replay.step(&input, TICK); }
+
+    assert_eq!(demo.tick_count(), TICKS as usize);
+    assert!(demo.matches(&replay));
+
+    // One more tick is enough to diverge.
+    replay.step(&Input::default(), TICK);
+    assert!(!demo.matches(&replay));
+}
+
+/// walks in circles and throws every kind of projectile in turn
+fn busy_input(tick: u32) -> Input {
+    let projectiles = [
+        Projectile::Ball,
+        Projectile::Sticky,
+        Projectile::Bouncy,
+        Projectile::Heavy,
+        Projectile::Gust
+    ];
+
+    Input {
+        look: vec2(2.0, 0.0),
+        moves: vec![Direction::Front],
+        is_sprinting: tick % 120 < 60,
+        select: tick.is_multiple_of(30)
+            .then(|| projectiles[(tick / 30) as usize % projectiles.len()]),
+        is_charging: tick % 30 < 20,
+        throw: tick % 30 == 20,
+        jump: tick.is_multiple_of(90),
+        ..Input::default()
+    }
+}
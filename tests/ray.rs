@@ -0,0 +1,139 @@
+//! what rays cast through the world hit first
+
+use game::sim::{World, Level};
+use game::sim::heightmap::Heightmap;
+use game::sim::level::FrogSpawn;
+use game::sim::mob::{Mob, frog};
+use game::sim::ray::Target;
+use game::sim::rules::Rule;
+use game::sim::solid::{Solid, Rise};
+
+use macroquad::prelude::*;
+
+/// Along x, ahead of the player: the frog, then the wall.
+const FROG_X: f32 = 4.0;
+const WALL_X: f32 = 8.0;
+
+#[test]
+fn hits_the_nearest_of_mobs_and_solids() {
+    let world = World::new(&level(), 0);
+    let origin = world.player.centre();
+
+    let hit = world.ray_cast(origin, Vec3::X, 100.0).unwrap();
+
+    assert_eq!(hit.target, Target::Frog(0));
+    assert!((hit.dist - (FROG_X - origin.x)).abs() < 1e-5);
+    assert_eq!(hit.normal, -Vec3::X);
+
+    // Out of reach.
+    assert!(world.ray_cast(origin, Vec3::X, 1.0).is_none());
+}
+
+#[test]
+fn skips_mobs_the_ray_starts_in() {
+    let world = World::new(&level(), 0);
+
+    // From the eyes, as for the crosshair.
+    let hit = world.aimed_at(100.0).unwrap();
+
+    assert_ne!(hit.target, Target::Player);
+    assert!(hit.dist > 0.0);
+
+    let hit = world.ray_cast(world.frogs[0].centre(), Vec3::X, 100.0)
+        .unwrap();
+
+    assert_eq!(hit.target, Target::Solid(0));
+    assert_eq!(hit.pos.x, WALL_X);
+}
+
+#[test]
+fn hits_ramps_on_their_slope() {
+    let world = World::new(&level(), 0);
+    let ramp = world.terrain.solids()[1];
+
+    let hit = world.ray_cast(vec3(-8.0, 5.0, 0.0), -Vec3::Y, 100.0).unwrap();
+
+    // Halfway up a ramp rising by 2 m.
+    assert_eq!(hit.target, Target::Solid(1));
+    assert!((hit.pos.y - 1.0).abs() < 1e-5, "{}", hit.pos);
+    assert!((hit.normal - ramp.normal()).length() < 1e-5);
+
+    // Under the slope, through the side of the box.
+    let hit = world.ray_cast(vec3(-3.0, 0.5, 0.0), -Vec3::X, 100.0).unwrap();
+
+    assert_eq!(hit.target, Target::Solid(1));
+    assert_eq!(hit.normal, Vec3::X);
+
+    // Over the low end, then into the slope as it rises.
+    let hit = world.ray_cast(vec3(-12.0, 1.5, 0.0), Vec3::X, 100.0).unwrap();
+
+    assert_eq!(hit.target, Target::Solid(1));
+    assert!((hit.pos.x - -7.0).abs() < 1e-5, "{}", hit.pos);
+    assert!((hit.normal - ramp.normal()).length() < 1e-5);
+}
+
+#[test]
+fn hits_the_ground_where_nothing_is_in_the_way() {
+    let world = World::new(&level(), 0);
+
+    let hit = world.ray_cast(vec3(20.0, 5.0, 20.0), -Vec3::Y, 100.0)
+        .unwrap();
+
+    assert_eq!(hit.target, Target::Ground);
+    assert_eq!(hit.dist, 5.0);
+    assert_eq!(hit.normal, Vec3::Y);
+
+    assert!(world.terrain.has_line_of_sight(
+        vec3(20.0, 1.0, 20.0), vec3(-20.0, 1.0, 20.0)
+    ));
+    assert!(!world.terrain.has_line_of_sight(
+        vec3(0.0, 1.0, 0.0), vec3(20.0, 1.0, 0.0)
+    ));
+}
+
+#[test]
+fn never_hits_heightmaps_from_below() {
+    let map = Heightmap::new(100.0, 3, 3, vec![2.0; 9]);
+    let is_land = |_, _| true;
+
+    let below = vec3(0.0, 1.0, 0.0);
+
+    assert!(map.ray_hit(below, -Vec3::Y, 100.0, is_land).is_none());
+    assert!(map.ray_hit(below, Vec3::Y, 100.0, is_land).is_none());
+    assert!(map.ray_hit(below, Vec3::X, 100.0, is_land).is_none());
+
+    let dist = map.ray_hit(vec3(0.0, 5.0, 0.0), -Vec3::Y, 100.0, is_land)
+        .unwrap();
+
+    assert!((dist - 3.0).abs() < 1e-3, "{dist}");
+}
+
+/// the player at the origin looking along x, a frog ahead of them, a wall
+/// behind it, and a ramp behind the player rising along x
+fn level() -> Level {
+    let frog = FrogSpawn {
+        pos: vec3(FROG_X, 0.0, -0.5),
+        color: GREEN,
+        mass: frog::DEFAULT_MASS,
+        restitution: frog::DEFAULT_RESTITUTION,
+        is_calm: true
+    };
+    let wall = Solid {
+        min: vec3(WALL_X, 0.0, -5.0),
+        max: vec3(WALL_X + 1.0, 4.0, 5.0),
+        ramp: None
+    };
+    let ramp = Solid {
+        min: vec3(-10.0, 0.0, -2.0),
+        max: vec3(-6.0, 2.0, 2.0),
+        ramp: Some(Rise::PosX)
+    };
+
+    Level {
+        frogs: vec![frog],
+        solids: vec![wall, ramp],
+        // The default one refers to more frogs.
+        win: Rule::TimeElapsed(f32::INFINITY),
+        ..Level::default()
+    }
+}
@@ -0,0 +1,117 @@
+//! snapshots of the 3d simulation, and the saves they are written to
+
+use game::sim::{World, Input, Level, TICK};
+use game::sim::mob::ball::{Ball, Kind};
+use game::sim::save::{self, Snapshot, SaveError};
+
+use macroquad::prelude::*;
+
+use ringbuf::Rb;
+
+use std::path::PathBuf;
+use std::{env, fs, process};
+
+const SEED: u64 = 0x5eed;
+
+#[test]
+fn restores_its_own_snapshot() {
+    let mut world = world_with_ball();
+    let snap = world.snapshot();
+
+    world.step(&Input::default(), TICK);
+    world.restore(&snap).unwrap();
+
+    assert_eq!(world.snapshot().hash(), snap.hash());
+}
+
+#[test]
+fn rejects_balls_stuck_to_missing_frogs() {
+    let mut world = world_with_ball();
+    let mut snap = world.snapshot();
+
+    snap.balls[0].stuck_to = Some(world.frogs.len());
+
+    let err = world.restore(&snap).unwrap_err();
+
+    assert!(matches!(err, SaveError::Mismatch(_)), "{err}");
+}
+
+#[test]
+fn rejects_masses_that_are_not_positive() {
+    let mut world = world_with_ball();
+
+    for mass in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+        let mut snap = world.snapshot();
+
+        snap.frogs[0].mass = mass;
+        assert!(matches!(world.restore(&snap), Err(SaveError::Invalid(_))));
+
+        let mut snap = world.snapshot();
+
+        snap.balls[0].mass = mass;
+        assert!(matches!(world.restore(&snap), Err(SaveError::Invalid(_))));
+    }
+}
+
+#[test]
+fn rejects_living_players_without_lives() {
+    let mut world = world_with_ball();
+    let mut snap = world.snapshot();
+
+    snap.lives = 0;
+    assert!(matches!(world.restore(&snap), Err(SaveError::Invalid(_))));
+
+    // Game over.
+    snap.is_player_dead = true;
+    world.restore(&snap).unwrap();
+    assert!(world.is_player_dead());
+}
+
+#[test]
+fn reads_saves_of_the_current_version() {
+    let snap = world_with_ball().snapshot();
+    let path = temp_path("current");
+
+    snap.write(&path).unwrap();
+
+    let read = Snapshot::read(&path);
+
+    fs::remove_file(&path).unwrap();
+    assert_eq!(read.unwrap().hash(), snap.hash());
+}
+
+#[test]
+fn rejects_saves_of_other_versions() {
+    for version in [save::VERSION - 1, save::VERSION + 1] {
+        let mut snap = world_with_ball().snapshot();
+        let path = temp_path(&version.to_string());
+
+        snap.version = version;
+        snap.write(&path).unwrap();
+
+        let read = Snapshot::read(&path);
+
+        fs::remove_file(&path).unwrap();
+
+        match read {
+            Err(SaveError::Version { found, version: expected }) => {
+                assert_eq!((found, expected), (version, save::VERSION));
+            }
+            Err(e) => panic!("wrong error: {e}"),
+            Ok(_) => panic!("read a save of version {version}")
+        }
+    }
+}
+
+fn world_with_ball() -> World {
+    let mut world = World::new(&Level::default(), SEED);
+    let ball = Ball::new(vec3(0.0, 5.0, 0.0), Vec3::ZERO, Kind::Sticky);
+
+    world.balls.push_overwrite(ball);
+    world
+}
+
+/// unique to the test process, as tests run in parallel
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("save-{}-{name}.toml", process::id()))
+}
@@ -0,0 +1,157 @@
+//! steps the 3d simulation without a window, as a player never could
+
+use game::sim::{World, Input, Level, TICK};
+use game::sim::mob::Mob;
+use game::sim::mob::ball::{Ball, Kind};
+use game::sim::mob::frog::Frog;
+use game::sim::mob::player::Direction;
+use game::sim::rules::Rule;
+
+use macroquad::prelude::*;
+
+use ringbuf::Rb;
+
+const SEED: u64 = 0x5eed;
+/// Long enough for frogs to land and hop around.
+const TICKS: u32 = 600;
+
+#[test]
+fn frogs_land_and_stay_on_the_ground() {
+    let mut world = World::new(&Level::default(), SEED);
+    let spawns: Vec<Vec3> = world.frogs.iter().map(Frog::pos).collect();
+
+    step(&mut world, &Input::default(), TICKS);
+
+    for (frog, spawn) in world.frogs.iter().zip(spawns) {
+        let pos = frog.pos();
+
+        assert!(pos.is_finite());
+        assert!(pos.y >= 0.0 && pos.y < spawn.y, "frog at {pos}");
+        assert!(!world.terrain.is_outside_bounds(pos), "frog at {pos}");
+    }
+}
+
+#[test]
+fn balls_count_hits_towards_the_win_rule() {
+    let level = Level {
+        win: Rule::Hits { frog: None, count: 1 },
+        ..Level::default()
+    };
+    let mut world = World::new(&level, SEED);
+
+    // Settles the frogs, which spawn in the air.
+    step(&mut world, &Input::default(), 60);
+    assert!(!world.goals().is_met);
+
+    let start = world.frogs[0].centre() - Vec3::X * 2.0;
+
+    world.balls.push_overwrite(Ball::new(start, Vec3::X * 40.0, Kind::Regular));
+    step(&mut world, &Input::default(), 10);
+
+    assert_eq!(world.hits(0), 1);
+    assert!(world.goals().is_met);
+    assert!(world.player.is_victorious);
+}
+
+#[test]
+fn fast_balls_hit_frogs_they_pass_through_within_a_tick() {
+    // Faster than the fastest throw, 2 m per tick through a 1 m frog.
+    const SPEED: f32 = 120.0;
+
+    let mut world = World::new(&Level::default(), SEED);
+
+    step(&mut world, &Input::default(), 60);
+
+    let start = world.frogs[0].centre() - Vec3::X * 3.0;
+
+    let ball = Ball::new(start, Vec3::X * SPEED, Kind::Regular);
+
+    world.balls.push_overwrite(ball);
+    step(&mut world, &Input::default(), 5);
+
+    assert_eq!(world.hits(0), 1);
+}
+
+#[test]
+fn charge_is_lost_when_let_go_without_throwing() {
+    let mut world = World::new(&Level::default(), SEED);
+    let charging = Input { is_charging: true, ..Input::default() };
+
+    step(&mut world, &charging, 30);
+    assert!(world.player.charge() > 0.0);
+
+    step(&mut world, &Input::default(), 1);
+    assert_eq!(world.player.charge(), 0.0);
+    assert_eq!(world.balls.len(), 0);
+}
+
+#[test]
+fn default_level_is_not_won_by_waiting() {
+    let mut world = World::new(&Level::default(), SEED);
+
+    step(&mut world, &Input::default(), TICKS);
+
+    let goals = world.goals();
+
+    assert!(!goals.is_met);
+    assert!((0.0..1.0).contains(&goals.progress));
+    assert!(!world.player.is_victorious);
+}
+
+#[test]
+fn players_off_the_edge_die_within_seconds() {
+    let level = Level::default();
+    let level = Level {
+        // Past the wall, over nothing.
+        spawn: vec3(level.width, 0.0, 0.0),
+        void_depth: 50.0,
+        ..level
+    };
+    let mut world = World::new(&level, SEED);
+
+    step(&mut world, &Input::default(), 60);
+    assert!(!world.is_player_dead());
+
+    step(&mut world, &Input::default(), TICKS);
+    assert!(world.is_player_dead());
+    assert_eq!(world.lives(), level.lives - 1);
+}
+
+#[test]
+fn same_seed_and_input_play_out_the_same() {
+    let run = |seed| {
+        let mut world = World::new(&Level::default(), seed);
+
+        for i in 0..TICKS {
+            world.step(&busy_input(i), TICK);
+        }
+
+        world
+    };
+
+    let (a, b) = (run(SEED), run(SEED));
+
+    assert_eq!(a.snapshot().hash(), b.snapshot().hash());
+
+    // Frogs hop at random, so they end up elsewhere with another seed.
+    let positions = |w: &World| -> Vec<Vec3> {
+        w.frogs.iter().map(Frog::pos).collect()
+    };
+
+    assert_ne!(positions(&a), positions(&run(SEED + 1)));
+}
+
+/// walks, looks around, and throws a charged ball every second
+fn busy_input(tick: u32) -> Input {
+    Input {
+        look: vec2(3.0, 0.0),
+        moves: vec![Direction::Front],
+        is_charging: tick % 60 < 30,
+        throw: tick % 60 == 30,
+        ..Input::default()
+    }
+}
+
+fn step(world: &mut World, input: &Input, ticks: u32) {
+    for _ in 0..ticks { world.step(input, TICK); }
+}